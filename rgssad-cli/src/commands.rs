@@ -1,2 +1,36 @@
+pub mod list;
 pub mod pack;
+pub mod rekey;
 pub mod unpack;
+pub mod verify;
+
+/// An error for a command that finished, but failed to process some entries.
+///
+/// This maps to a different exit code than other errors, which stop a command early.
+#[derive(Debug)]
+pub struct PartialFailure {
+    /// The number of entries that failed.
+    pub num_failed: usize,
+}
+
+impl std::fmt::Display for PartialFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to process {} entries", self.num_failed)
+    }
+}
+
+impl std::error::Error for PartialFailure {}
+
+/// Format an error followed by each of its sources, separated by `": "`.
+///
+/// This matches the alternate format of [`anyhow::Error`], for errors that are not owned.
+pub fn format_error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message.push_str(": ");
+        message.push_str(&error.to_string());
+        source = error.source();
+    }
+    message
+}
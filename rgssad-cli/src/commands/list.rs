@@ -0,0 +1,122 @@
+use anyhow::Context;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+
+#[derive(Debug, argh::FromArgs)]
+#[argh(
+    subcommand,
+    name = "list",
+    description = "list the files in an rgssad archive without extracting them"
+)]
+pub struct Options {
+    #[argh(positional, description = "the archive to list")]
+    pub input: PathBuf,
+
+    #[argh(
+        switch,
+        short = 'l',
+        long = "long",
+        description = "also print the offset of each file's data and a totals line"
+    )]
+    pub long: bool,
+
+    #[argh(
+        switch,
+        long = "report-duplicates",
+        description = "also print groups of files with identical contents, and the bytes they waste"
+    )]
+    pub report_duplicates: bool,
+}
+
+pub fn exec(options: Options) -> anyhow::Result<()> {
+    let input_file = File::open(&options.input)
+        .with_context(|| format!("failed to open \"{}\"", options.input.display()))?;
+    let mut reader = rgssad::Reader::new(BufReader::new(input_file));
+    reader
+        .read_header()
+        .context("failed to read archive header")?;
+
+    if options.long {
+        print_long(&mut reader)?;
+    } else {
+        let entries = reader.entries().context("failed to read file headers")?;
+        print!("{}", rgssad::manifest::render_table(&entries));
+    }
+
+    if options.report_duplicates {
+        // Finding duplicates reads every file, so start over from the beginning.
+        let mut input_file = reader.into_inner();
+        input_file.seek(SeekFrom::Start(0))?;
+        let mut reader = rgssad::Reader::new(input_file);
+        reader
+            .read_header()
+            .context("failed to read archive header")?;
+
+        let groups = reader.duplicate_groups().context("failed to read files")?;
+        print_duplicates(&groups);
+    }
+
+    Ok(())
+}
+
+fn print_long<R>(reader: &mut rgssad::Reader<R>) -> anyhow::Result<()>
+where
+    R: std::io::Read + Seek,
+{
+    let headers = reader
+        .file_headers()
+        .context("failed to read file headers")?;
+
+    let offset_width = headers
+        .iter()
+        .map(|header| format!("{:#x}", header.offset).len())
+        .chain(std::iter::once("Offset".len()))
+        .max()
+        .unwrap_or(0);
+    let size_width = headers
+        .iter()
+        .map(|header| header.size.to_string().len())
+        .chain(std::iter::once("Size".len()))
+        .max()
+        .unwrap_or(0);
+
+    println!("{:>offset_width$}  {:>size_width$}  Name", "Offset", "Size");
+    let mut total_size = 0_u64;
+    for header in headers.iter() {
+        println!(
+            "{:>offset_width$}  {:>size_width$}  {}",
+            format!("{:#x}", header.offset),
+            header.size,
+            header.name
+        );
+        total_size += u64::from(header.size);
+    }
+    println!("{} files, {total_size} bytes", headers.len());
+
+    Ok(())
+}
+
+fn print_duplicates(groups: &[rgssad::DuplicateGroup]) {
+    let mut total_wasted_bytes = 0_u64;
+    for group in groups.iter() {
+        let wasted_bytes = group.wasted_bytes();
+        println!();
+        println!(
+            "{} files with identical contents, {} bytes each, {wasted_bytes} bytes wasted:",
+            group.names.len(),
+            group.size
+        );
+        for name in group.names.iter() {
+            println!("  {name}");
+        }
+        total_wasted_bytes += wasted_bytes;
+    }
+    println!();
+    println!(
+        "{} duplicate groups, {total_wasted_bytes} bytes wasted",
+        groups.len()
+    );
+}
@@ -1,8 +1,15 @@
+use anyhow::bail;
 use anyhow::Context;
 use std::fs::File;
 use std::path::PathBuf;
+use walkdir::DirEntry;
 use walkdir::WalkDir;
 
+/// File names that are skipped with `--no-hidden`, in addition to dotfiles.
+///
+/// These are created by file managers and never belong in a game archive.
+const JUNK_FILE_NAMES: &[&str] = &["Thumbs.db", "desktop.ini"];
+
 #[derive(Debug, argh::FromArgs)]
 #[argh(
     subcommand,
@@ -15,19 +22,70 @@ pub struct Options {
 
     #[argh(positional, description = "the output file path")]
     pub output: PathBuf,
+
+    #[argh(
+        switch,
+        long = "no-hidden",
+        description = "skip dotfiles, dot-directories, and common junk files like \"Thumbs.db\""
+    )]
+    pub no_hidden: bool,
+
+    #[argh(
+        switch,
+        short = 'f',
+        long = "force",
+        description = "overwrite the output file if it already exists"
+    )]
+    pub force: bool,
+
+    #[argh(
+        switch,
+        short = 'q',
+        long = "quiet",
+        description = "do not print each file as it is packed or skipped"
+    )]
+    pub quiet: bool,
 }
 
 pub fn exec(options: Options) -> anyhow::Result<()> {
-    let mut output_file = File::options()
-        .create_new(true)
-        .write(true)
-        .open(&options.output)
-        .with_context(|| format!("failed to open \"{}\"", options.output.display()))?;
+    let mut open_options = File::options();
+    open_options.write(true);
+    if options.force {
+        open_options.create(true).truncate(true);
+    } else {
+        open_options.create_new(true);
+    }
+    let mut output_file = match open_options.open(&options.output) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+            bail!(
+                "\"{}\" already exists, pass --force to overwrite it",
+                options.output.display()
+            );
+        }
+        Err(error) => {
+            return Err(error)
+                .with_context(|| format!("failed to open \"{}\"", options.output.display()));
+        }
+    };
     let mut writer = rgssad::Writer::new(&mut output_file);
     writer.write_header()?;
 
-    for file_entry in WalkDir::new(&options.input).sort_by_file_name() {
+    let mut num_skipped = 0_usize;
+    let mut walker = WalkDir::new(&options.input).sort_by_file_name().into_iter();
+    while let Some(file_entry) = walker.next() {
         let file_entry = file_entry?;
+        if options.no_hidden && is_hidden(&file_entry) {
+            if !options.quiet {
+                println!("Skipping \"{}\"", file_entry.path().display());
+            }
+            num_skipped += 1;
+            if file_entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+
         let file_type = file_entry.file_type();
         let path = file_entry.path();
 
@@ -46,21 +104,37 @@ pub fn exec(options: Options) -> anyhow::Result<()> {
             )
         })?;
 
-        println!("Packing \"{relative_path_str}\"");
-
-        let file =
-            File::open(path).with_context(|| format!("failed to open \"{}\"", path.display()))?;
-        let file_metadata = file
-            .metadata()
-            .with_context(|| format!("failed to get metadata for \"{}\"", path.display()))?;
-        let file_size = u32::try_from(file_metadata.len())
-            .with_context(|| format!("file \"{}\" is too large", path.display()))?;
+        if !options.quiet {
+            println!("Packing \"{relative_path_str}\"");
+        }
 
-        writer.write_file(relative_path_str, file_size, file)?;
+        writer
+            .write_path(relative_path_str, path)
+            .with_context(|| format!("failed to pack \"{}\"", path.display()))?;
     }
     writer.finish()?;
 
     output_file.sync_all()?;
 
+    if options.no_hidden {
+        println!("Skipped {num_skipped} hidden or junk entries");
+    }
+
     Ok(())
 }
+
+/// Check if a walked entry is a dotfile, dot-directory, or known junk file.
+///
+/// The root of the walk is never considered hidden.
+fn is_hidden(entry: &DirEntry) -> bool {
+    if entry.depth() == 0 {
+        return false;
+    }
+
+    let file_name = entry.file_name();
+    let Some(file_name) = file_name.to_str() else {
+        return false;
+    };
+
+    file_name.starts_with('.') || JUNK_FILE_NAMES.contains(&file_name)
+}
@@ -0,0 +1,109 @@
+use anyhow::Context;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+#[derive(Debug, argh::FromArgs)]
+#[argh(
+    subcommand,
+    name = "rekey",
+    description = "re-encrypt an rgssad archive with a different initial key"
+)]
+pub struct Options {
+    #[argh(positional, description = "the archive to re-encrypt")]
+    pub input: PathBuf,
+
+    #[argh(positional, description = "the output file path")]
+    pub output: PathBuf,
+
+    #[argh(
+        option,
+        description = "the initial key of the input archive, in hex",
+        from_str_fn(parse_hex_key),
+        default = "rgssad::DEFAULT_KEY"
+    )]
+    pub from: u32,
+
+    #[argh(
+        option,
+        description = "the initial key of the output archive, in hex",
+        from_str_fn(parse_hex_key)
+    )]
+    pub to: u32,
+
+    #[argh(
+        switch,
+        short = 'q',
+        long = "quiet",
+        description = "do not print each file as it is re-encrypted"
+    )]
+    pub quiet: bool,
+}
+
+fn parse_hex_key(value: &str) -> Result<u32, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u32::from_str_radix(digits, 16).map_err(|error| format!("invalid hex key \"{value}\": {error}"))
+}
+
+pub fn exec(options: Options) -> anyhow::Result<()> {
+    let input_file = File::open(&options.input)
+        .with_context(|| format!("failed to open \"{}\"", options.input.display()))?;
+    let archive_len = input_file.metadata()?.len();
+    let mut reader = rgssad::Reader::with_key(BufReader::new(input_file), options.from);
+    reader.set_archive_len(Some(archive_len));
+    reader
+        .read_header()
+        .context("failed to read archive header")?;
+
+    // A wrong key usually produces garbage file names or sizes,
+    // so check every file header before creating the output file.
+    reader
+        .len()
+        .with_context(|| format!("failed to read entry with key {:#010X}", options.from))?;
+
+    let mut output_file = File::options()
+        .create_new(true)
+        .write(true)
+        .open(&options.output)
+        .with_context(|| format!("failed to open \"{}\"", options.output.display()))?;
+
+    let result = rekey(&mut reader, &mut output_file, &options);
+    if result.is_err() {
+        // Do not leave a partial archive behind, as it would block retrying.
+        drop(output_file);
+        let _ = std::fs::remove_file(&options.output);
+    }
+    result
+}
+
+fn rekey(
+    reader: &mut rgssad::Reader<BufReader<File>>,
+    output_file: &mut File,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let mut writer = rgssad::Writer::with_key(&mut *output_file, options.to);
+    writer.write_header()?;
+
+    while let Some(file) = reader
+        .read_file()
+        .with_context(|| format!("failed to read entry with key {:#010X}", options.from))?
+    {
+        if !options.quiet {
+            println!("Re-encrypting \"{}\"", file.name());
+        }
+
+        let file_name = file.name().to_string();
+        let file_size = file.size();
+        writer
+            .write_file(&file_name, file_size, file)
+            .with_context(|| format!("failed to write \"{file_name}\""))?;
+    }
+    writer.finish()?;
+
+    output_file.sync_all()?;
+
+    Ok(())
+}
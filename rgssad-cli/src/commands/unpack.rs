@@ -1,10 +1,8 @@
 use anyhow::bail;
-use anyhow::ensure;
 use anyhow::Context;
 use std::fs::File;
 use std::io::Read;
-use std::io::Write;
-use std::path::Component as PathComponent;
+use std::io::Seek;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -22,13 +20,70 @@ pub struct Options {
         default = "PathBuf::from(\".\")"
     )]
     pub output: PathBuf,
+
+    #[argh(
+        option,
+        long = "max-output",
+        description = "the maximum total number of bytes to extract, to guard against untrusted archives"
+    )]
+    pub max_output: Option<u64>,
+
+    #[argh(
+        option,
+        long = "max-entries",
+        description = "the maximum number of entries to read, to guard against untrusted archives"
+    )]
+    pub max_entries: Option<usize>,
+
+    #[argh(
+        switch,
+        long = "show-offsets",
+        description = "print the offset of each file's data in the archive"
+    )]
+    pub show_offsets: bool,
+
+    #[argh(
+        switch,
+        long = "to-tar",
+        description = "write a tar archive to stdout instead of extracting"
+    )]
+    pub to_tar: bool,
+
+    #[argh(
+        option,
+        long = "file",
+        description = "only extract the file with this name, where \"/\" also matches \"\\\""
+    )]
+    pub file: Option<String>,
+
+    #[argh(
+        switch,
+        short = 'q',
+        long = "quiet",
+        description = "do not print each file as it is extracted"
+    )]
+    pub quiet: bool,
 }
 
 pub fn exec(options: Options) -> anyhow::Result<()> {
-    let file = File::open(options.input)?;
+    if options.to_tar && options.file.is_some() {
+        bail!("--file cannot be used with --to-tar");
+    }
+
+    let file = File::open(&options.input)?;
     let mut reader = rgssad::Reader::new(file);
+    reader.set_max_total_output(options.max_output);
+    reader.set_max_entries(options.max_entries);
     reader.read_header()?;
 
+    if options.to_tar {
+        let stdout = std::io::stdout().lock();
+        reader
+            .into_tar(std::io::BufWriter::new(stdout))
+            .context("failed to write tar archive")?;
+        return Ok(());
+    }
+
     std::fs::create_dir_all(&options.output).with_context(|| {
         format!(
             "failed to create directory at \"{}\"",
@@ -40,92 +95,97 @@ pub fn exec(options: Options) -> anyhow::Result<()> {
     // This exists, as we just created it.
     let output = std::fs::canonicalize(&options.output)?;
 
-    let mut last_error = Ok(());
+    if let Some(name) = options.file.as_deref() {
+        return extract_single(&mut reader, &output, name, &options);
+    }
+
+    let mut report = rgssad::ExtractReport::default();
     while let Some(mut file) = reader.read_file()? {
-        println!("Extracting \"{}\"", file.name());
+        print_extracting(&file, &options);
 
-        // Sanitize and build path
-        let out_path = match construct_out_path(&output, file.name().as_ref()) {
-            Ok(out_path) => out_path,
-            Err(error) => {
-                eprintln!("  {error}, skipping");
-                last_error =
-                    Err(error).with_context(|| format!("failed to sanitize \"{}\"", file.name()));
-                continue;
-            }
-        };
-
-        match extract_file(&mut file, &out_path) {
-            Ok(()) => {}
-            Err(error) => {
-                eprintln!("  {error}, skipping");
-                last_error = Err(error).with_context(|| {
-                    format!(
-                        "failed to extract \"{}\" to \"{}\"",
-                        file.name(),
-                        out_path.display()
-                    )
-                });
-                continue;
-            }
+        let name = file.name().to_string();
+        match rgssad::extract::extract_entry(&output, &name, &mut file) {
+            Ok(_out_path) => report.extracted.push(name),
+            Err(error) => report.failed.push((name, error)),
         }
     }
 
-    last_error
+    println!("Extracted {} files", report.num_extracted());
+    if !report.is_success() {
+        eprintln!("Failed to extract {} files:", report.num_failed());
+        for (name, error) in report.failed.iter() {
+            eprintln!(
+                "  \"{name}\": {}",
+                crate::commands::format_error_chain(error)
+            );
+        }
+        bail!(crate::commands::PartialFailure {
+            num_failed: report.num_failed(),
+        });
+    }
+
+    Ok(())
 }
 
-fn construct_out_path(out_dir: &Path, file_path: &Path) -> anyhow::Result<PathBuf> {
-    let mut out_path = out_dir.to_path_buf();
-    let mut depth = 0_u32;
-
-    for component in file_path.components() {
-        match component {
-            PathComponent::Prefix(_) => {
-                bail!("encountered prefix in path");
-            }
-            PathComponent::RootDir => {
-                bail!("encountered root dir in path");
-            }
-            PathComponent::CurDir => {}
-            PathComponent::ParentDir => {
-                depth = depth.checked_sub(1).context("path goes above out path")?;
-            }
-            PathComponent::Normal(path) => {
-                depth = depth.checked_add(1).context("path depth overflow")?;
-
-                let path_has_prefix = Path::new(path)
-                    .components()
-                    .any(|path| matches!(path, PathComponent::Prefix(_)));
-                let path_has_root = Path::new(path)
-                    .components()
-                    .any(|path| matches!(path, PathComponent::RootDir));
-
-                ensure!(!path_has_prefix, "path component has prefix");
-                ensure!(!path_has_root, "path component has root");
-
-                out_path.push(path);
-            }
-        }
+/// Print the name of a file as it is extracted, unless `--quiet` was passed.
+fn print_extracting<R>(file: &rgssad::reader::File<'_, R>, options: &Options) {
+    if options.quiet {
+        return;
     }
 
-    Ok(out_path)
+    if options.show_offsets {
+        println!(
+            "Extracting \"{}\" (offset {:#x})",
+            file.name(),
+            file.offset()
+        );
+    } else {
+        println!("Extracting \"{}\"", file.name());
+    }
 }
 
-fn extract_file(entry: &mut impl Read, out_path: &Path) -> anyhow::Result<()> {
-    if let Some(parent_dir) = out_path.parent() {
-        std::fs::create_dir_all(parent_dir)
-            .with_context(|| format!("failed to create dir at \"{}\"", parent_dir.display()))?;
+/// Extract only the file named `name`.
+///
+/// Archives made on Windows use `\` as a separator, so `/` is tried as `\` if there is no exact match.
+fn extract_single<R>(
+    reader: &mut rgssad::Reader<R>,
+    output: &Path,
+    name: &str,
+    options: &Options,
+) -> anyhow::Result<()>
+where
+    R: Read + Seek,
+{
+    // This scans and caches every file header, so the lookups below only need to seek.
+    let num_entries = reader.len().context("failed to read file headers")?;
+
+    if let Some(mut file) = reader.read_file_by_name(name)? {
+        return extract_file(output, &mut file, options);
     }
 
-    let mut file = File::options()
-        .create_new(true)
-        .write(true)
-        .open(out_path)?;
+    let windows_name = name.replace('/', "\\");
+    if windows_name != name {
+        if let Some(mut file) = reader.read_file_by_name(&windows_name)? {
+            return extract_file(output, &mut file, options);
+        }
+    }
 
-    std::io::copy(entry, &mut file)?;
+    bail!("no file named \"{name}\" was found in {num_entries} entries");
+}
 
-    file.flush()?;
-    file.sync_all()?;
+/// Extract a file found by [`extract_single`].
+fn extract_file<R>(
+    output: &Path,
+    file: &mut rgssad::reader::File<'_, R>,
+    options: &Options,
+) -> anyhow::Result<()>
+where
+    R: Read + Seek,
+{
+    print_extracting(file, options);
+    let name = file.name().to_string();
+    rgssad::extract::extract_entry(output, &name, file)
+        .with_context(|| format!("failed to extract \"{name}\""))?;
 
     Ok(())
 }
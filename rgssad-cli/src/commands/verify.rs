@@ -0,0 +1,84 @@
+use anyhow::bail;
+use anyhow::Context;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::PathBuf;
+
+#[derive(Debug, argh::FromArgs)]
+#[argh(
+    subcommand,
+    name = "verify",
+    description = "check that every file in an rgssad archive can be read"
+)]
+pub struct Options {
+    #[argh(positional, description = "the archive to verify")]
+    pub input: PathBuf,
+
+    #[argh(
+        switch,
+        short = 'q',
+        long = "quiet",
+        description = "do not print each file as it is verified"
+    )]
+    pub quiet: bool,
+}
+
+pub fn exec(options: Options) -> anyhow::Result<()> {
+    let input_file = File::open(&options.input)
+        .with_context(|| format!("failed to open \"{}\"", options.input.display()))?;
+    let archive_len = input_file.metadata()?.len();
+    let mut reader = rgssad::Reader::new(BufReader::new(input_file));
+    // This makes file sizes that extend past the end of the archive an error while reading headers.
+    reader.set_archive_len(Some(archive_len));
+    reader
+        .read_header()
+        .context("failed to read archive header")?;
+
+    let mut num_ok = 0_usize;
+    let mut num_failed = 0_usize;
+    let mut buffer = Vec::new();
+    loop {
+        let mut file = match reader.read_file() {
+            Ok(Some(file)) => file,
+            Ok(None) => break,
+            Err(error) => {
+                // Without a valid file header, the position of the next file is unknown.
+                eprintln!(
+                    "failed to read file header: {}",
+                    crate::commands::format_error_chain(&error)
+                );
+                num_failed += 1;
+                break;
+            }
+        };
+
+        buffer.clear();
+        let name = file.name().to_string();
+
+        // If this fails, the next file header is still found,
+        // as the reader seeks past the rest of this file's data.
+        match file.read_to_end(&mut buffer) {
+            Ok(_) => {
+                if !options.quiet {
+                    println!("Verified \"{name}\"");
+                }
+                num_ok += 1;
+            }
+            Err(error) => {
+                eprintln!(
+                    "\"{name}\": {}",
+                    crate::commands::format_error_chain(&error)
+                );
+                num_failed += 1;
+            }
+        }
+    }
+
+    println!("{num_ok} files OK, {num_failed} failed");
+    if num_failed != 0 {
+        bail!(crate::commands::PartialFailure { num_failed });
+    }
+
+    Ok(())
+}
@@ -12,11 +12,38 @@ struct Options {
 enum Subcommand {
     Unpack(self::commands::unpack::Options),
     Pack(self::commands::pack::Options),
+    Rekey(self::commands::rekey::Options),
+    List(self::commands::list::Options),
+    Verify(self::commands::verify::Options),
 }
 
-fn main() -> anyhow::Result<()> {
+/// The exit code for a successful run.
+const EXIT_SUCCESS: u8 = 0;
+
+/// The exit code for a run that finished, but failed to process some entries.
+const EXIT_PARTIAL_FAILURE: u8 = 1;
+
+/// The exit code for a run that failed early, like when an archive could not be opened or parsed.
+const EXIT_FATAL: u8 = 2;
+
+fn main() -> std::process::ExitCode {
     let options: Options = argh::from_env();
 
+    match run(options) {
+        Ok(()) => std::process::ExitCode::from(EXIT_SUCCESS),
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+
+            if error.is::<self::commands::PartialFailure>() {
+                std::process::ExitCode::from(EXIT_PARTIAL_FAILURE)
+            } else {
+                std::process::ExitCode::from(EXIT_FATAL)
+            }
+        }
+    }
+}
+
+fn run(options: Options) -> anyhow::Result<()> {
     match options.subcommand {
         Subcommand::Unpack(options) => {
             self::commands::unpack::exec(options)?;
@@ -24,6 +51,15 @@ fn main() -> anyhow::Result<()> {
         Subcommand::Pack(options) => {
             self::commands::pack::exec(options)?;
         }
+        Subcommand::Rekey(options) => {
+            self::commands::rekey::exec(options)?;
+        }
+        Subcommand::List(options) => {
+            self::commands::list::exec(options)?;
+        }
+        Subcommand::Verify(options) => {
+            self::commands::verify::exec(options)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,302 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rgssad-cli-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("failed to create temp dir");
+    dir
+}
+
+fn write_archive(path: &PathBuf, files: &[(&str, &[u8])]) {
+    let mut writer = rgssad::Writer::new_vec();
+    for (name, data) in files {
+        let len = u32::try_from(data.len()).expect("file data too large");
+        writer
+            .write_file(name, len, *data)
+            .expect("failed to write file");
+    }
+    let archive = writer.into_vec().expect("failed to finish");
+    std::fs::write(path, archive).expect("failed to write archive");
+}
+
+fn unpack(input: &PathBuf, output: &PathBuf) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_rgssad-cli"))
+        .arg("unpack")
+        .arg(input)
+        .arg("-o")
+        .arg(output)
+        .arg("--quiet")
+        .output()
+        .expect("failed to run rgssad-cli")
+}
+
+#[test]
+fn unpack_success() {
+    let dir = temp_dir("unpack-success");
+    let input = dir.join("Game.rgssad");
+    write_archive(&input, &[("test.txt", b"test")]);
+
+    let output = unpack(&input, &dir.join("out"));
+    assert!(output.status.code() == Some(0));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Extracting"));
+    assert!(std::fs::read(dir.join("out/test.txt")).is_ok());
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn unpack_partial_failure() {
+    let dir = temp_dir("unpack-partial-failure");
+    let input = dir.join("Game.rgssad");
+    write_archive(&input, &[("good.txt", b"good"), ("../evil.txt", b"evil")]);
+
+    let output = unpack(&input, &dir.join("out"));
+    assert!(output.status.code() == Some(1));
+    // Each failed entry is reported once, in the summary.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.matches("../evil.txt").count() == 1, "{stderr}");
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn unpack_fatal() {
+    let dir = temp_dir("unpack-fatal");
+    let input = dir.join("Game.rgssad");
+    std::fs::write(&input, b"not an archive").expect("failed to write archive");
+
+    let output = unpack(&input, &dir.join("out"));
+    assert!(output.status.code() == Some(2));
+
+    let output = unpack(&dir.join("missing.rgssad"), &dir.join("out"));
+    assert!(output.status.code() == Some(2));
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn unpack_single_file() {
+    let dir = temp_dir("unpack-single-file");
+    let input = dir.join("Game.rgssad");
+    write_archive(&input, &[("a.txt", b"a"), ("b.txt", b"b")]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rgssad-cli"))
+        .arg("unpack")
+        .arg(&input)
+        .arg("-o")
+        .arg(dir.join("out"))
+        .args(["--file", "b.txt", "--show-offsets"])
+        .output()
+        .expect("failed to run rgssad-cli");
+    assert!(output.status.code() == Some(0));
+    assert!(std::fs::read(dir.join("out/b.txt")).expect("missing b.txt") == b"b");
+    assert!(!dir.join("out/a.txt").exists());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"b.txt\" (offset "));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rgssad-cli"))
+        .arg("unpack")
+        .arg(&input)
+        .arg("-o")
+        .arg(dir.join("out"))
+        .args(["--file", "c.txt"])
+        .output()
+        .expect("failed to run rgssad-cli");
+    assert!(output.status.code() == Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 entries"));
+
+    // "/" also matches the "\\" separator used by archives made on Windows.
+    let input = dir.join("Windows.rgssad");
+    write_archive(&input, &[("Data\\c.txt", b"c")]);
+    let output = Command::new(env!("CARGO_BIN_EXE_rgssad-cli"))
+        .arg("unpack")
+        .arg(&input)
+        .arg("-o")
+        .arg(dir.join("out"))
+        .args(["--file", "Data/c.txt", "--quiet"])
+        .output()
+        .expect("failed to run rgssad-cli");
+    assert!(output.status.code() == Some(0));
+
+    // The flags are checked before the archive is opened.
+    let output = Command::new(env!("CARGO_BIN_EXE_rgssad-cli"))
+        .arg("unpack")
+        .arg(dir.join("missing.rgssad"))
+        .args(["--file", "c.txt", "--to-tar"])
+        .output()
+        .expect("failed to run rgssad-cli");
+    assert!(output.status.code() == Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--to-tar"));
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn pack_existing_output() {
+    let dir = temp_dir("pack-existing-output");
+    let input = dir.join("in");
+    std::fs::create_dir_all(&input).expect("failed to create input dir");
+    std::fs::write(input.join("test.txt"), b"test").expect("failed to write file");
+    let output = dir.join("Game.rgssad");
+    std::fs::write(&output, b"existing").expect("failed to write file");
+
+    let pack = |force: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rgssad-cli"));
+        command.arg("pack").arg(&input).arg(&output).arg("--quiet");
+        if force {
+            command.arg("--force");
+        }
+        command.output().expect("failed to run rgssad-cli")
+    };
+
+    let result = pack(false);
+    assert!(result.status.code() == Some(2));
+    assert!(String::from_utf8_lossy(&result.stderr).contains("--force"));
+    assert!(std::fs::read(&output).expect("failed to read output") == b"existing");
+
+    let result = pack(true);
+    assert!(result.status.code() == Some(0));
+    let archive = std::fs::read(&output).expect("failed to read output");
+    assert!(archive.starts_with(b"RGSSAD"));
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn verify() {
+    let dir = temp_dir("verify");
+    let input = dir.join("Game.rgssad");
+    write_archive(&input, &[("a.txt", b"a"), ("b.txt", b"bb")]);
+
+    let verify = || {
+        Command::new(env!("CARGO_BIN_EXE_rgssad-cli"))
+            .arg("verify")
+            .arg(&input)
+            .arg("--quiet")
+            .output()
+            .expect("failed to run rgssad-cli")
+    };
+
+    let output = verify();
+    assert!(output.status.code() == Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2 files OK, 0 failed"));
+
+    // Cut off the last byte of the last file.
+    let mut archive = std::fs::read(&input).expect("failed to read archive");
+    archive.pop();
+    std::fs::write(&input, archive).expect("failed to write archive");
+
+    // The broken file is reported in the summary, instead of stopping the command early.
+    let output = verify();
+    assert!(output.status.code() == Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 files OK, 1 failed"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("extends past the end"));
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn unpack_existing_output_reason() {
+    let dir = temp_dir("unpack-existing-output-reason");
+    let input = dir.join("Game.rgssad");
+    write_archive(&input, &[("test.txt", b"test")]);
+
+    let output = unpack(&input, &dir.join("out"));
+    assert!(output.status.code() == Some(0));
+
+    // The OS error explains why the existing file was not overwritten.
+    let output = unpack(&input, &dir.join("out"));
+    assert!(output.status.code() == Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("an I/O error occured: "), "{stderr}");
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn rekey() {
+    const KEY: u32 = 0x12345678;
+
+    let dir = temp_dir("rekey");
+    let input = dir.join("Game.rgssad");
+    let files: &[(&str, &[u8])] = &[("a.txt", b"a"), ("Data/b.txt", b"bb")];
+    write_archive(&input, files);
+
+    let rekey = |input: &PathBuf, output: &PathBuf, from: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rgssad-cli"));
+        command
+            .arg("rekey")
+            .arg(input)
+            .arg(output)
+            .args(["--to", &format!("{KEY:x}")])
+            .arg("--quiet");
+        if let Some(from) = from {
+            command.args(["--from", from]);
+        }
+        command.output().expect("failed to run rgssad-cli")
+    };
+
+    let output_path = dir.join("Rekeyed.rgssad");
+    let output = rekey(&input, &output_path, None);
+    assert!(output.status.code() == Some(0));
+    assert!(output.stdout.is_empty());
+
+    let archive = std::fs::read(&output_path).expect("failed to read output");
+    let mut reader = rgssad::Reader::with_key(std::io::Cursor::new(archive), KEY);
+    reader.read_header().expect("failed to read header");
+    for (name, data) in files {
+        let mut file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(file.name() == *name);
+        let mut actual = Vec::new();
+        std::io::Read::read_to_end(&mut file, &mut actual).expect("failed to read file");
+        assert!(actual == *data);
+    }
+    assert!(reader.read_file().expect("failed to read file").is_none());
+
+    // A wrong input key is caught before the output file is created.
+    let output_path = dir.join("Wrong.rgssad");
+    let output = rekey(&input, &output_path, Some("1"));
+    assert!(output.status.code() == Some(2));
+    assert!(!output_path.exists());
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
+
+#[test]
+fn list_report_duplicates() {
+    let dir = temp_dir("list-report-duplicates");
+    let input = dir.join("Game.rgssad");
+    write_archive(
+        &input,
+        &[
+            ("a.txt", b"same"),
+            ("b.txt", b"other"),
+            ("Data\\c.txt", b"same"),
+            ("d.txt", b"same"),
+        ],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rgssad-cli"))
+        .arg("list")
+        .arg(&input)
+        .arg("--report-duplicates")
+        .output()
+        .expect("failed to run rgssad-cli");
+    assert!(output.status.code() == Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("3 files with identical contents, 4 bytes each, 8 bytes wasted:"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("  Data\\c.txt\n"), "{stdout}");
+    assert!(!stdout.contains("  b.txt\n"), "{stdout}");
+    assert!(
+        stdout.contains("1 duplicate groups, 8 bytes wasted"),
+        "{stdout}"
+    );
+
+    std::fs::remove_dir_all(&dir).expect("failed to clean up");
+}
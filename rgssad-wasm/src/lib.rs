@@ -71,16 +71,55 @@ impl Reader {
                 continue;
             }
 
-            // Wasm16 does not exist.
-            let mut buffer = Vec::with_capacity(usize::try_from(file.size()).unwrap());
-            file.read_to_end(&mut buffer)
-                .map_err(|error| JsError::new(&error.to_string()))?;
-            let data = Uint8Array::new_with_length(file.size());
-            data.copy_from(&buffer);
+            return File::from_reader(file_name, &mut file).map(Some);
+        }
+    }
+
+    /// Get the file at the given index.
+    ///
+    /// This rewinds to the start of the archive and skips over earlier files without decrypting them.
+    /// Returns `undefined` if the index is past the end of the archive.
+    /// After this is called, `readFile` continues from the file after this one.
+    #[wasm_bindgen(js_name = "readEntry")]
+    pub fn read_entry(&mut self, index: usize) -> Result<Option<File>, JsValue> {
+        let file = rewind_to_entry(&mut self.reader, index)
+            .map_err(|error| JsError::new(&error.to_string()))?;
+        let mut file = match file {
+            Some(file) => file,
+            None => {
+                return Ok(None);
+            }
+        };
 
-            return Ok(Some(File { file_name, data }));
+        let file_name = JsString::from(file.name());
+        File::from_reader(file_name, &mut file).map(Some)
+    }
+}
+
+/// Rewind `reader` to the start of the archive, then read the file at `index`.
+///
+/// Earlier files are skipped without being decrypted.
+/// This returns `None` if the index is past the end of the archive.
+fn rewind_to_entry(
+    reader: &mut rgssad::Reader<std::io::Cursor<Vec<u8>>>,
+    index: usize,
+) -> Result<Option<rgssad::reader::File<'_, std::io::Cursor<Vec<u8>>>>, rgssad::Error> {
+    let old_reader = std::mem::replace(
+        reader,
+        rgssad::Reader::new(std::io::Cursor::new(Vec::new())),
+    );
+    let mut bytes = old_reader.into_inner();
+    bytes.set_position(0);
+    *reader = rgssad::Reader::new(bytes);
+    reader.read_header()?;
+
+    for _ in 0..index {
+        if reader.read_file()?.is_none() {
+            return Ok(None);
         }
     }
+
+    reader.read_file()
 }
 
 /// A file from a [`Reader`].
@@ -93,6 +132,27 @@ pub struct File {
     data: Uint8Array,
 }
 
+impl File {
+    /// Read the rest of an archive file into a new [`File`].
+    fn from_reader<R>(
+        file_name: JsString,
+        file: &mut rgssad::reader::File<'_, R>,
+    ) -> Result<File, JsValue>
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let capacity = usize::try_from(file.size())
+            .map_err(|_| JsError::new("file is too large to fit in memory"))?;
+        let mut buffer = Vec::with_capacity(capacity);
+        file.read_to_end(&mut buffer)
+            .map_err(|error| JsError::new(&error.to_string()))?;
+        let data = Uint8Array::new_with_length(file.size());
+        data.copy_from(&buffer);
+
+        Ok(File { file_name, data })
+    }
+}
+
 #[wasm_bindgen]
 impl File {
     /// Get the file name.
@@ -106,4 +166,136 @@ impl File {
     pub fn data(&self) -> Uint8Array {
         self.data.clone()
     }
+
+    /// Take the file data, consuming this file.
+    ///
+    /// After this is called, this file object can no longer be used.
+    #[wasm_bindgen(js_name = "takeData")]
+    pub fn take_data(self) -> Uint8Array {
+        self.data
+    }
+}
+
+/// An Archive Writer
+///
+/// The archive is built in memory.
+#[wasm_bindgen]
+pub struct Writer {
+    writer: rgssad::Writer<std::io::Cursor<Vec<u8>>>,
+}
+
+#[wasm_bindgen]
+impl Writer {
+    /// Make a new [`Writer`].
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Writer {
+        Self {
+            writer: rgssad::Writer::new_vec(),
+        }
+    }
+
+    /// Write the archive header.
+    ///
+    /// Calling this is optional, as writing a file or finishing writes the header if needed.
+    #[wasm_bindgen(js_name = "writeHeader")]
+    pub fn write_header(&mut self) -> Result<(), JsError> {
+        self.writer
+            .write_header()
+            .map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Write a file.
+    ///
+    /// The file size is the length of the data.
+    #[wasm_bindgen(js_name = "writeFile")]
+    pub fn write_file(&mut self, name: &str, data: &Uint8Array) -> Result<(), JsError> {
+        let size = data.length();
+        let data = data.to_vec();
+        self.writer
+            .write_file(name, size, &*data)
+            .map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Finish writing the archive.
+    ///
+    /// This fails if a file was only partially written.
+    /// The header is written first if needed, so an archive with no files is still valid.
+    pub fn finish(&mut self) -> Result<(), JsError> {
+        self.writer
+            .write_header()
+            .and_then(|()| self.writer.finish())
+            .map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Get a copy of the archive written so far.
+    ///
+    /// Call `finish` first to get a complete archive.
+    #[wasm_bindgen(js_name = "toUint8Array")]
+    pub fn to_uint8_array(&self) -> Uint8Array {
+        Uint8Array::from(self.writer.get_ref().get_ref().as_slice())
+    }
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rewind_to_entry_reads_any_index() {
+        let files: [(&str, &[u8]); 3] = [("a", b"first"), ("b", b"second"), ("c", b"third")];
+        let mut writer = rgssad::Writer::new_vec();
+        for (name, data) in files {
+            let len = u32::try_from(data.len()).unwrap();
+            writer
+                .write_file(name, len, data)
+                .expect("failed to write file");
+        }
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let mut reader = rgssad::Reader::new(std::io::Cursor::new(archive));
+        reader.read_header().expect("failed to read header");
+
+        // Read out of order, including after reaching the end.
+        for index in [2, 0, 1, 2] {
+            let mut file = rewind_to_entry(&mut reader, index)
+                .expect("failed to read entry")
+                .expect("missing entry");
+            let (name, data) = files[index];
+            assert!(file.name() == name);
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer).expect("failed to read file");
+            assert!(buffer == data);
+        }
+
+        // Reading continues after the requested file.
+        rewind_to_entry(&mut reader, 1)
+            .expect("failed to read entry")
+            .expect("missing entry");
+        let file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(file.name() == "c");
+
+        assert!(rewind_to_entry(&mut reader, 3)
+            .expect("failed to read entry")
+            .is_none());
+    }
+
+    #[test]
+    fn writer_finish_empty() {
+        let mut writer = Writer::new();
+        assert!(writer.finish().is_ok());
+
+        let archive = writer.writer.get_ref().get_ref().clone();
+        let mut reader = rgssad::Reader::new(std::io::Cursor::new(archive));
+        reader.read_header().expect("failed to read header");
+        assert!(reader.read_file().expect("failed to read file").is_none());
+    }
 }
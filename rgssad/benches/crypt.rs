@@ -0,0 +1,41 @@
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+use criterion::Throughput;
+use std::hint::black_box;
+
+/// Build an archive with many empty files with long names, so that reading headers is dominated by name decryption.
+fn build_archive(num_files: usize, name_len: usize) -> Vec<u8> {
+    let mut writer = rgssad::Writer::new_vec();
+    for i in 0..num_files {
+        let mut file_name = format!("Graphics\\Pictures\\{i:05}_");
+        while file_name.len() < name_len {
+            file_name.push('a');
+        }
+        writer
+            .write_file(&file_name, 0, std::io::empty())
+            .expect("failed to write file");
+    }
+    writer.into_vec().expect("failed to finish")
+}
+
+fn name_crypt(c: &mut Criterion) {
+    let num_files = 1000;
+    let name_len = 200;
+    let archive = build_archive(num_files, name_len);
+
+    let mut group = c.benchmark_group("name_crypt");
+    group.throughput(Throughput::Bytes(
+        u64::try_from(num_files * name_len).unwrap(),
+    ));
+    group.bench_function("manifest", |b| {
+        b.iter(|| {
+            let mut reader = rgssad::Reader::new(std::io::Cursor::new(black_box(&archive)));
+            reader.manifest().expect("failed to build manifest")
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, name_crypt);
+criterion_main!(benches);
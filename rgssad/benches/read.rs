@@ -0,0 +1,50 @@
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+use criterion::Throughput;
+use std::hint::black_box;
+use std::io::Read;
+
+/// Build an archive with a few large files, so that reading is dominated by file data.
+fn build_archive(num_files: usize, file_size: usize) -> Vec<u8> {
+    let file_data: Vec<u8> = (0..file_size).map(|i| i as u8).collect();
+    let file_size_u32 = u32::try_from(file_size).unwrap();
+
+    let mut writer = rgssad::Writer::new_vec();
+    for i in 0..num_files {
+        writer
+            .write_file(&format!("Data\\{i:02}.rvdata"), file_size_u32, &*file_data)
+            .expect("failed to write file");
+    }
+    writer.into_vec().expect("failed to finish")
+}
+
+fn read_data(c: &mut Criterion) {
+    let num_files = 16;
+    let file_size = 1024 * 1024;
+    let archive = build_archive(num_files, file_size);
+
+    let mut group = c.benchmark_group("read_data");
+    group.throughput(Throughput::Bytes(
+        u64::try_from(num_files * file_size).unwrap(),
+    ));
+    group.bench_function("sequential", |b| {
+        let mut buffer = vec![0; 64 * 1024];
+        b.iter(|| {
+            let mut reader = rgssad::Reader::new(std::io::Cursor::new(black_box(&archive)));
+            while let Some(mut file) = reader.read_file().expect("failed to read file") {
+                loop {
+                    let n = file.read(&mut buffer).expect("failed to read file data");
+                    if n == 0 {
+                        break;
+                    }
+                    black_box(&buffer[..n]);
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read_data);
+criterion_main!(benches);
@@ -0,0 +1,266 @@
+/// An encryption scheme for archives.
+///
+/// Every method encrypts or decrypts in place, and rotates the key as needed.
+/// The standard scheme is [`StandardCrypt`].
+/// Implement this to experiment with modified variants of the format, like ones with a different key schedule.
+/// Readers and writers are generic over this, so the standard scheme has no overhead.
+pub trait Crypt: Clone {
+    /// Encrypt or decrypt a u32, like a file name length or file size.
+    fn crypt_u32(&self, key: &mut u32, n: u32) -> u32;
+
+    /// Encrypt or decrypt a file name.
+    fn crypt_name(&self, key: &mut u32, bytes: &mut [u8]);
+
+    /// Encrypt or decrypt file data.
+    ///
+    /// `counter` is the position within the current 4 byte key block.
+    /// Both `key` and `counter` carry over between calls for the same file.
+    fn crypt_data(&self, key: &mut u32, counter: &mut u8, data: &mut [u8]);
+}
+
+/// The standard encryption scheme.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StandardCrypt;
+
+impl Crypt for StandardCrypt {
+    fn crypt_u32(&self, key: &mut u32, n: u32) -> u32 {
+        crypt_u32(key, n)
+    }
+
+    fn crypt_name(&self, key: &mut u32, bytes: &mut [u8]) {
+        crypt_name_bytes(key, bytes);
+    }
+
+    fn crypt_data(&self, key: &mut u32, counter: &mut u8, data: &mut [u8]) {
+        crypt_file_data(key, counter, data);
+    }
+}
+
+/// Rotate the key once.
+///
+/// Key rotation is defined as `new_key = (old_key * 7) + 3`, with wrapping arithmetic.
+pub fn rotate_key(key: u32) -> u32 {
+    key.overflowing_mul(7).0.overflowing_add(3).0
+}
+
+/// Rotate the key `n` times.
+///
+/// This runs in `O(log n)` time, instead of rotating the key `n` times one by one.
+pub fn rotate_key_n(key: u32, mut n: u64) -> u32 {
+    // A rotation is the affine map `k -> (k * 7) + 3`.
+    // Affine maps compose into affine maps,
+    // so we can build the `n`th power by repeated squaring.
+    let mut mul = 1_u32;
+    let mut add = 0_u32;
+    let mut base_mul = 7_u32;
+    let mut base_add = 3_u32;
+    while n > 0 {
+        if n & 1 == 1 {
+            mul = base_mul.wrapping_mul(mul);
+            add = base_mul.wrapping_mul(add).wrapping_add(base_add);
+        }
+        base_add = base_mul.wrapping_mul(base_add).wrapping_add(base_add);
+        base_mul = base_mul.wrapping_mul(base_mul);
+        n >>= 1;
+    }
+
+    mul.wrapping_mul(key).wrapping_add(add)
+}
+
+/// Advance the key over a v1 file header, without needing the header bytes.
+///
+/// A file header rotates the key once for the name length,
+/// once per name byte,
+/// and once for the file size,
+/// so the key is rotated `file_name_len + 2` times.
+/// File data does not persistently rotate the key.
+///
+/// Calling this for every preceding file header,
+/// starting from the archive's initial key,
+/// produces the key used to decrypt the next file header.
+pub fn advance_key_over(key: &mut u32, file_name_len: usize) {
+    // A usize always fits in a u64 on supported platforms.
+    let file_name_len = u64::try_from(file_name_len).unwrap();
+    *key = rotate_key_n(*key, file_name_len + 2);
+}
+
+/// Encrypt or decrypt a whole file's data in place.
+///
+/// `key` is the key at the start of the file data,
+/// as reported by [`crate::Reader::entry_keys`].
+pub fn crypt_entry_data(key: u32, data: &mut [u8]) {
+    let mut key = key;
+    let mut counter = 0;
+    crypt_file_data(&mut key, &mut counter, data);
+}
+
+/// Encrypt or decrypt an u32, and rotate the key as needed.
+pub(crate) fn crypt_u32(key: &mut u32, mut n: u32) -> u32 {
+    n ^= *key;
+    *key = rotate_key(*key);
+    n
+}
+
+/// The number of name bytes crypted per chunk in [`crypt_name_bytes`].
+const NAME_CHUNK_LEN: usize = 8;
+
+/// The affine maps for rotating the low byte of a key 0 to [`NAME_CHUNK_LEN`] times.
+///
+/// The low byte of a rotated key only depends on the low byte of the previous key,
+/// so rotating the low byte `n` times is the map `low -> (low * mul) + add`, modulo 256.
+const NAME_KEY_MAPS: [(u8, u8); NAME_CHUNK_LEN + 1] = {
+    let mut maps = [(1_u8, 0_u8); NAME_CHUNK_LEN + 1];
+    let mut i = 1;
+    while i < maps.len() {
+        let (mul, add) = maps[i - 1];
+        maps[i] = (mul.wrapping_mul(7), add.wrapping_mul(7).wrapping_add(3));
+        i += 1;
+    }
+    maps
+};
+
+/// Encrypt of decrypt a file name, and rotate the key as needed.
+pub(crate) fn crypt_name_bytes(key: &mut u32, bytes: &mut [u8]) {
+    // Each name byte is crypted with only the low byte of the key.
+    // Instead of rotating the whole key one byte at a time,
+    // derive the low bytes for a chunk at once,
+    // then rotate the whole key in one step at the end.
+    //
+    // We mask with 0xFF, this cannot exceed the bounds of a u8.
+    let mut low = u8::try_from(*key & 0xFF).unwrap();
+
+    let mut chunks = bytes.chunks_exact_mut(NAME_CHUNK_LEN);
+    for chunk in &mut chunks {
+        for (byte, (mul, add)) in chunk.iter_mut().zip(NAME_KEY_MAPS) {
+            *byte ^= low.wrapping_mul(mul).wrapping_add(add);
+        }
+        let (mul, add) = NAME_KEY_MAPS[NAME_CHUNK_LEN];
+        low = low.wrapping_mul(mul).wrapping_add(add);
+    }
+    for byte in chunks.into_remainder() {
+        *byte ^= low;
+        low = low.wrapping_mul(7).wrapping_add(3);
+    }
+
+    // A usize always fits in a u64 on supported platforms.
+    *key = rotate_key_n(*key, u64::try_from(bytes.len()).unwrap());
+}
+
+/// Encrypt or decrypt the encrypted file data, and rotate the key as needed.
+pub(crate) fn crypt_file_data(key: &mut u32, counter: &mut u8, buffer: &mut [u8]) {
+    // TODO: We can possibly be more efficient here.
+    // If we are able to cast this to a slice of u32s,
+    // we can crypt that instead and use this byte-wise impl only at the end.
+    for byte in buffer.iter_mut() {
+        *byte ^= key.to_le_bytes()[usize::from(*counter)];
+        if *counter == 3 {
+            *key = rotate_key(*key);
+        }
+        *counter = (*counter + 1) % 4;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DEFAULT_KEY;
+
+    /// A crypt that leaves everything as plaintext.
+    #[derive(Debug, Clone)]
+    struct IdentityCrypt;
+
+    impl Crypt for IdentityCrypt {
+        fn crypt_u32(&self, _key: &mut u32, n: u32) -> u32 {
+            n
+        }
+
+        fn crypt_name(&self, _key: &mut u32, _bytes: &mut [u8]) {}
+
+        fn crypt_data(&self, _key: &mut u32, _counter: &mut u8, _data: &mut [u8]) {}
+    }
+
+    #[test]
+    fn identity_crypt_round_trip() {
+        let mut writer = crate::Writer::with_crypt(Vec::new(), DEFAULT_KEY, IdentityCrypt);
+        writer
+            .write_file("Data\\Test.rvdata", 9, &b"plaintext"[..])
+            .expect("failed to write file");
+        writer.finish().expect("failed to flush");
+        let archive = writer.into_inner();
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&crate::MAGIC);
+        expected.push(crate::VERSION);
+        expected.extend_from_slice(&16_u32.to_le_bytes());
+        expected.extend_from_slice(b"Data\\Test.rvdata");
+        expected.extend_from_slice(&9_u32.to_le_bytes());
+        expected.extend_from_slice(b"plaintext");
+        assert!(archive == expected);
+
+        let mut reader =
+            crate::Reader::with_crypt(std::io::Cursor::new(archive), DEFAULT_KEY, IdentityCrypt);
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        assert!(files == [("Data\\Test.rvdata".to_string(), b"plaintext".to_vec())]);
+    }
+
+    #[test]
+    fn rotate_key_n_matches_sequential() {
+        let mut key = DEFAULT_KEY;
+        for n in 0..1024 {
+            assert!(rotate_key_n(DEFAULT_KEY, n) == key, "mismatch at {n}");
+            key = rotate_key(key);
+        }
+    }
+
+    /// The straightforward implementation of [`crypt_name_bytes`].
+    fn crypt_name_bytes_scalar(key: &mut u32, bytes: &mut [u8]) {
+        for byte in bytes.iter_mut() {
+            *byte ^= u8::try_from(*key & 0xFF).unwrap();
+            *key = rotate_key(*key);
+        }
+    }
+
+    #[test]
+    fn crypt_name_bytes_matches_scalar() {
+        let mut seed = DEFAULT_KEY;
+        for len in 0..300 {
+            for key in [0, 1, 0xFF, DEFAULT_KEY, u32::MAX, seed] {
+                let input: Vec<u8> = (0..len).map(|i| (i * 31 + len) as u8).collect();
+
+                let mut expected_key = key;
+                let mut expected = input.clone();
+                crypt_name_bytes_scalar(&mut expected_key, &mut expected);
+
+                let mut actual_key = key;
+                let mut actual = input;
+                crypt_name_bytes(&mut actual_key, &mut actual);
+
+                assert!(
+                    actual == expected,
+                    "data mismatch for len {len}, key {key:#x}"
+                );
+                assert!(
+                    actual_key == expected_key,
+                    "key mismatch for len {len}, key {key:#x}"
+                );
+            }
+            seed = rotate_key(seed ^ len);
+        }
+    }
+
+    #[test]
+    fn advance_key_over_matches_crypt() {
+        for file_name_len in [0, 1, 3, 4, 17, 255, 4096] {
+            let mut expected = DEFAULT_KEY;
+            crypt_u32(&mut expected, 0);
+            crypt_name_bytes(&mut expected, &mut vec![0; file_name_len]);
+            crypt_u32(&mut expected, 0);
+
+            let mut key = DEFAULT_KEY;
+            advance_key_over(&mut key, file_name_len);
+
+            assert!(key == expected, "mismatch for name len {file_name_len}");
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+/// A directory in a tree of archive entries.
+///
+/// Archive entry names are flat paths, usually separated with `\`.
+/// This groups them into nested directories.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DirNode {
+    /// The total size of all files in this directory, including subdirectories.
+    pub size: u64,
+
+    /// The total number of files in this directory, including subdirectories.
+    pub num_entries: usize,
+
+    /// The subdirectories, keyed by name.
+    pub dirs: BTreeMap<String, DirNode>,
+
+    /// The files in this directory, keyed by name, with their sizes.
+    pub files: BTreeMap<String, u32>,
+}
+
+impl DirNode {
+    /// Create a new, empty [`DirNode`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a file into this tree.
+    ///
+    /// Both `\` and `/` are treated as separators.
+    /// Empty components are ignored.
+    pub fn insert(&mut self, name: &str, size: u32) {
+        let mut components = name.split(['\\', '/']).filter(|c| !c.is_empty());
+        let Some(mut last) = components.next() else {
+            return;
+        };
+
+        let mut node = self;
+        node.size += u64::from(size);
+        node.num_entries += 1;
+        for component in components {
+            node = node.dirs.entry(last.to_string()).or_default();
+            node.size += u64::from(size);
+            node.num_entries += 1;
+            last = component;
+        }
+        node.files.insert(last.to_string(), size);
+    }
+
+    /// Get a subdirectory by path, using the same separator rules as [`DirNode::insert`].
+    pub fn get_dir(&self, path: &str) -> Option<&DirNode> {
+        let mut node = self;
+        for component in path.split(['\\', '/']).filter(|c| !c.is_empty()) {
+            node = node.dirs.get(component)?;
+        }
+        Some(node)
+    }
+}
+
+/// A view of an archive's entries as a filesystem-like directory tree.
+///
+/// This is meant to ease porting code written against [`std::fs::read_dir`].
+/// Directories are synthesized from the path prefixes of entry names.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ArchiveDir {
+    root: DirNode,
+}
+
+impl ArchiveDir {
+    /// Create a new [`ArchiveDir`] from a directory tree.
+    ///
+    /// A tree can be built with [`crate::Reader::directory_tree`].
+    pub fn new(root: DirNode) -> Self {
+        Self { root }
+    }
+
+    /// Get the underlying directory tree.
+    pub fn root(&self) -> &DirNode {
+        &self.root
+    }
+
+    /// List the entries of the directory at `path`.
+    ///
+    /// Use an empty path for the root directory.
+    /// Subdirectories are listed before files, and each are sorted by name.
+    /// Returns a [`std::io::ErrorKind::NotFound`] error if the directory does not exist.
+    pub fn read_dir(&self, path: &str) -> std::io::Result<ReadDir<'_>> {
+        let node = self
+            .root
+            .get_dir(path)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))?;
+
+        Ok(ReadDir {
+            dirs: node.dirs.iter(),
+            files: node.files.iter(),
+        })
+    }
+}
+
+/// An iterator over the entries of an [`ArchiveDir`] directory.
+#[derive(Debug)]
+pub struct ReadDir<'a> {
+    dirs: std::collections::btree_map::Iter<'a, String, DirNode>,
+    files: std::collections::btree_map::Iter<'a, String, u32>,
+}
+
+impl Iterator for ReadDir<'_> {
+    type Item = DirEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((name, node)) = self.dirs.next() {
+            return Some(DirEntry {
+                name: name.clone(),
+                size: node.size,
+                is_file: false,
+            });
+        }
+
+        let (name, size) = self.files.next()?;
+        Some(DirEntry {
+            name: name.clone(),
+            size: u64::from(*size),
+            is_file: true,
+        })
+    }
+}
+
+/// An entry in an [`ArchiveDir`] directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// The name of this entry, without its parent path.
+    pub name: String,
+
+    /// The file size, or the total size of all files in a directory.
+    pub size: u64,
+
+    /// Whether this entry is a file, as opposed to a directory.
+    pub is_file: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::VX_TEST_GAME;
+
+    #[test]
+    fn archive_dir_read_dir() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = crate::Reader::new(std::io::Cursor::new(file));
+        let tree = reader
+            .directory_tree()
+            .expect("failed to build directory tree");
+        let archive_dir = ArchiveDir::new(tree);
+
+        let entries: Vec<_> = archive_dir
+            .read_dir("")
+            .expect("failed to read root dir")
+            .collect();
+        let data = entries
+            .iter()
+            .find(|entry| entry.name == "Data")
+            .expect("missing \"Data\" dir");
+        assert!(!data.is_file);
+        assert!(data.size == archive_dir.root().dirs["Data"].size);
+        assert!(entries.len() == archive_dir.root().dirs.len() + archive_dir.root().files.len());
+
+        let data_entries: Vec<_> = archive_dir
+            .read_dir("Data")
+            .expect("failed to read \"Data\" dir")
+            .collect();
+        assert!(!data_entries.is_empty());
+        assert!(data_entries.iter().all(|entry| entry.is_file));
+
+        let error = archive_dir
+            .read_dir("Missing")
+            .expect_err("dir should not exist");
+        assert!(error.kind() == std::io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::Error;
+use crate::Reader;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::Write;
+use std::path::Component as PathComponent;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The result of extracting every entry of an archive.
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    /// The names of the entries that were extracted.
+    pub extracted: Vec<String>,
+
+    /// The names of the entries that failed to extract, with the error that caused them to fail.
+    pub failed: Vec<(String, Error)>,
+}
+
+impl ExtractReport {
+    /// Get the number of entries that were extracted.
+    pub fn num_extracted(&self) -> usize {
+        self.extracted.len()
+    }
+
+    /// Get the number of entries that failed to extract.
+    pub fn num_failed(&self) -> usize {
+        self.failed.len()
+    }
+
+    /// Returns true if no entries failed to extract.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Extract every entry of an archive into `out_dir`.
+///
+/// `out_dir` is created if it does not exist.
+/// Entries that fail to extract, like those with unsafe paths, are skipped and recorded in the report as failed.
+/// This includes entries whose data fails to read partway through,
+/// and their partially written files are removed, as with [`extract_entry`].
+/// Errors reading a file header stop extraction and are returned.
+pub fn extract_all<R>(reader: R, out_dir: &Path) -> Result<ExtractReport, Error>
+where
+    R: Read + Seek,
+{
+    let mut reader = Reader::new(reader);
+    reader.read_header()?;
+
+    std::fs::create_dir_all(out_dir)?;
+
+    // This creates a UNC path on Windows, which is helpful when dealing with long paths.
+    // This exists, as we just created it.
+    let out_dir = std::fs::canonicalize(out_dir)?;
+
+    let mut report = ExtractReport::default();
+    while let Some(mut file) = reader.read_file()? {
+        let name = file.name().to_string();
+        match extract_entry(&out_dir, &name, &mut file) {
+            Ok(_out_path) => report.extracted.push(name),
+            Err(error) => report.failed.push((name, error)),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Extract a single entry named `name` into `out_dir`, returning the path it was written to.
+///
+/// The path is built with [`sanitize_entry_path`].
+/// Missing parent directories are created.
+/// This will not overwrite existing files.
+/// If reading or writing the data fails, the partially written file is removed.
+pub fn extract_entry<R>(out_dir: &Path, name: &str, data: &mut R) -> Result<PathBuf, Error>
+where
+    R: Read + ?Sized,
+{
+    let out_path = sanitize_entry_path(out_dir, name)?;
+
+    if let Some(parent_dir) = out_path.parent() {
+        std::fs::create_dir_all(parent_dir)?;
+    }
+
+    let mut file = File::options()
+        .create_new(true)
+        .write(true)
+        .open(&out_path)?;
+
+    let result = std::io::copy(data, &mut file)
+        .and_then(|_| file.flush())
+        .and_then(|_| file.sync_all());
+    if let Err(error) = result {
+        // The file was created above, so it is not someone else's file.
+        drop(file);
+        let _ = std::fs::remove_file(&out_path);
+        return Err(error.into());
+    }
+
+    Ok(out_path)
+}
+
+/// Build the output path for an entry named `name` under `out_dir`.
+///
+/// This rejects names that would escape `out_dir`,
+/// like absolute paths or paths with too many `..` components.
+pub fn sanitize_entry_path(out_dir: &Path, name: &str) -> Result<PathBuf, Error> {
+    let mut out_path = out_dir.to_path_buf();
+    let mut depth = 0_u32;
+
+    for component in Path::new(name).components() {
+        match component {
+            PathComponent::Prefix(_) => {
+                return Err(Error::InvalidEntryPath {
+                    reason: "encountered prefix in path",
+                });
+            }
+            PathComponent::RootDir => {
+                return Err(Error::InvalidEntryPath {
+                    reason: "encountered root dir in path",
+                });
+            }
+            PathComponent::CurDir => {}
+            PathComponent::ParentDir => {
+                depth = depth.checked_sub(1).ok_or(Error::InvalidEntryPath {
+                    reason: "path goes above out path",
+                })?;
+            }
+            PathComponent::Normal(path) => {
+                depth = depth.checked_add(1).ok_or(Error::InvalidEntryPath {
+                    reason: "path depth overflow",
+                })?;
+
+                let path_has_prefix = Path::new(path)
+                    .components()
+                    .any(|path| matches!(path, PathComponent::Prefix(_)));
+                let path_has_root = Path::new(path)
+                    .components()
+                    .any(|path| matches!(path, PathComponent::RootDir));
+
+                if path_has_prefix {
+                    return Err(Error::InvalidEntryPath {
+                        reason: "path component has prefix",
+                    });
+                }
+                if path_has_root {
+                    return Err(Error::InvalidEntryPath {
+                        reason: "path component has root",
+                    });
+                }
+
+                out_path.push(path);
+            }
+        }
+    }
+
+    Ok(out_path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::VX_TEST_GAME;
+
+    #[test]
+    fn sanitize_entry_path_rejects_escapes() {
+        let out_dir = Path::new("out");
+
+        let path = sanitize_entry_path(out_dir, "Data/./Actors.rvdata").expect("path is safe");
+        assert!(path == Path::new("out/Data/Actors.rvdata"));
+
+        let path = sanitize_entry_path(out_dir, "Data/../Game.ini").expect("path is safe");
+        assert!(path == Path::new("out/Data/Game.ini"));
+
+        for name in ["../Game.ini", "Data/../../Game.ini", "/etc/passwd"] {
+            let error = sanitize_entry_path(out_dir, name).expect_err("path should be unsafe");
+            assert!(matches!(error, Error::InvalidEntryPath { .. }), "{name}");
+        }
+    }
+
+    #[test]
+    fn extract_all_vx() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let out_dir =
+            std::env::temp_dir().join(format!("rgssad-extract-all-vx-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&out_dir);
+
+        let report =
+            extract_all(std::io::Cursor::new(file.clone()), &out_dir).expect("failed to extract");
+        assert!(report.is_success(), "{:?}", report.failed);
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let mut num_files = 0;
+        while let Some(mut file) = reader.read_file().expect("failed to read file") {
+            let mut expected = Vec::new();
+            file.read_to_end(&mut expected)
+                .expect("failed to read file");
+
+            let out_path =
+                sanitize_entry_path(&out_dir, file.name()).expect("failed to build path");
+            let actual = std::fs::read(out_path).expect("failed to read extracted file");
+            assert!(actual == expected);
+
+            num_files += 1;
+        }
+        assert!(report.num_extracted() == num_files);
+
+        // Existing files are not overwritten.
+        let file = reader.into_inner().into_inner();
+        let report = extract_all(std::io::Cursor::new(file), &out_dir).expect("failed to extract");
+        assert!(report.num_extracted() == 0);
+        assert!(report.num_failed() == num_files);
+
+        std::fs::remove_dir_all(&out_dir).expect("failed to clean up");
+    }
+
+    #[test]
+    fn extract_entry_removes_partial_file() {
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("read failed"))
+            }
+        }
+
+        let out_dir = std::env::temp_dir().join(format!(
+            "rgssad-extract-entry-removes-partial-file-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&out_dir);
+
+        let mut data = (&b"partial"[..]).chain(FailingReader);
+        let error = extract_entry(&out_dir, "test.txt", &mut data).expect_err("read should fail");
+        assert!(matches!(error, Error::Io(_)));
+        assert!(!out_dir.join("test.txt").exists());
+
+        std::fs::remove_dir_all(&out_dir).expect("failed to clean up");
+    }
+
+    #[test]
+    fn extract_all_traversal() {
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("good.txt", 4, &b"good"[..])
+            .expect("failed to write file");
+        writer
+            .write_file("../evil.txt", 4, &b"evil"[..])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let out_dir = std::env::temp_dir().join(format!(
+            "rgssad-extract-all-traversal-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&out_dir);
+
+        let report = extract_all(std::io::Cursor::new(archive), &out_dir.join("out"))
+            .expect("failed to extract");
+        assert!(!report.is_success());
+        assert!(report.extracted == ["good.txt"]);
+        assert!(report.num_failed() == 1);
+        let (name, error) = &report.failed[0];
+        assert!(name == "../evil.txt");
+        assert!(matches!(error, Error::InvalidEntryPath { .. }));
+        assert!(!out_dir.join("evil.txt").exists());
+
+        std::fs::remove_dir_all(&out_dir).expect("failed to clean up");
+    }
+}
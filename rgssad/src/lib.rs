@@ -1,16 +1,41 @@
 // #[warn(clippy::arithmetic_side_effects)]
 
+/// Key schedule and encryption primitives.
+pub mod crypt;
+/// Grouping archive entries into a directory tree.
+pub mod dir_tree;
+/// Extracting archives to the filesystem.
+pub mod extract;
+/// Archive manifests.
+pub mod manifest;
+/// File name normalization.
+pub mod name;
 /// The archive reader.
 pub mod reader;
+/// RGSS versions and their archive extensions.
+pub mod rgss_version;
 /// sans-io state machines for reading and writing.
 pub mod sans_io;
+/// Writing archives split across multiple volumes.
+pub mod split;
 /// Tokio adapters for archive readers and writers.
 #[cfg(feature = "tokio")]
 pub mod tokio;
 /// The archive writer.
 pub mod writer;
 
+pub use self::dir_tree::ArchiveDir;
+pub use self::dir_tree::DirNode;
+pub use self::extract::ExtractReport;
+pub use self::manifest::DuplicateGroup;
+pub use self::manifest::EntryInfo;
+pub use self::manifest::Manifest;
+pub use self::name::NameNormalization;
+pub use self::reader::ProgressEvent;
 pub use self::reader::Reader;
+pub use self::reader::StepResult;
+pub use self::rgss_version::RgssVersion;
+pub use self::split::SplitWriter;
 #[cfg(feature = "tokio")]
 pub use self::tokio::TokioReader;
 #[cfg(feature = "tokio")]
@@ -23,10 +48,10 @@ const MAGIC_LEN: usize = 7;
 const MAGIC: [u8; MAGIC_LEN] = *b"RGSSAD\0";
 /// The file version
 const VERSION: u8 = 1;
-/// The size of the header.
-const HEADER_LEN: usize = MAGIC_LEN + 1;
+/// The size of the archive header, in bytes.
+pub const HEADER_LEN: usize = MAGIC_LEN + 1;
 /// The default encryption key.
-const DEFAULT_KEY: u32 = 0xDEADCAFE;
+pub const DEFAULT_KEY: u32 = 0xDEADCAFE;
 /// The maximum file name len.
 ///
 /// This was chosen arbirtarily,
@@ -37,9 +62,25 @@ const DEFAULT_KEY: u32 = 0xDEADCAFE;
 const MAX_FILE_NAME_LEN: u32 = 4096;
 /// The size of a u32, in bytes.
 const U32_LEN: usize = 4;
+/// The smallest internal buffer capacity of the sans-io state machines.
+///
+/// This is the size of the largest possible file header, which must fit in the buffer at once.
+pub const MIN_BUFFER_CAPACITY: usize = (U32_LEN * 2) + MAX_FILE_NAME_LEN as usize;
 
 /// The library error type
+///
+/// New variants may be added in the future,
+/// so matches outside of this crate need a catch-all arm:
+///
+/// ```compile_fail
+/// fn is_io(error: &rgssad::Error) -> bool {
+///     match error {
+///         rgssad::Error::Io(_) => true,
+///     }
+/// }
+/// ```
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// An I/O error occured.
     Io(std::io::Error),
@@ -49,6 +90,39 @@ pub enum Error {
 
     /// There was an error with the sans-io state machine.
     SansIo(self::sans_io::Error),
+
+    /// An entry path is unsafe to extract.
+    InvalidEntryPath {
+        /// Why the path is unsafe, like `"path goes above out path"`.
+        reason: &'static str,
+    },
+
+    /// The archive was not completely read or written.
+    NotFinished,
+
+    /// A file with the same name was already written.
+    DuplicateFileName {
+        /// The file name.
+        name: String,
+    },
+
+    /// A file was not completely written.
+    IncompleteFile {
+        /// The file name.
+        name: String,
+
+        /// The number of bytes of file data that were not written.
+        remaining: u32,
+    },
+
+    /// A file's data is too long to fit in an archive, as sizes are stored as a `u32`.
+    FileDataTooLong {
+        /// The size of the file data, in bytes.
+        size: u64,
+
+        /// The path of the file, if it was read from one.
+        path: Option<std::path::PathBuf>,
+    },
 }
 
 impl std::fmt::Display for Error {
@@ -57,6 +131,21 @@ impl std::fmt::Display for Error {
             Self::Io(_error) => write!(f, "an I/O error occured"),
             Self::InvalidState => write!(f, "user error, invalid internal state"),
             Self::SansIo(error) => error.fmt(f),
+            Self::InvalidEntryPath { reason } => write!(f, "invalid entry path: {reason}"),
+            Self::NotFinished => write!(f, "the archive was not completely read or written"),
+            Self::DuplicateFileName { name } => {
+                write!(f, "a file named \"{name}\" was already written")
+            }
+            Self::IncompleteFile { name, remaining } => {
+                write!(f, "file \"{name}\" is missing {remaining} bytes of data")
+            }
+            Self::FileDataTooLong { size, path } => {
+                match path {
+                    Some(path) => write!(f, "file \"{}\" is too large", path.display())?,
+                    None => write!(f, "file data is too large")?,
+                }
+                write!(f, ", {size} bytes is over the limit of {} bytes", u32::MAX)
+            }
         }
     }
 }
@@ -83,36 +172,6 @@ impl From<self::sans_io::Error> for Error {
     }
 }
 
-/// Encrypt or decrypt an u32, and rotate the key as needed.
-fn crypt_u32(key: &mut u32, mut n: u32) -> u32 {
-    n ^= *key;
-    *key = key.overflowing_mul(7).0.overflowing_add(3).0;
-    n
-}
-
-/// Encrypt of decrypt a file name, and rotate the key as needed.
-fn crypt_name_bytes(key: &mut u32, bytes: &mut [u8]) {
-    for byte in bytes.iter_mut() {
-        // We mask with 0xFF, this cannot exceed the bounds of a u8.
-        *byte ^= u8::try_from(*key & 0xFF).unwrap();
-        *key = key.overflowing_mul(7).0.overflowing_add(3).0;
-    }
-}
-
-/// Encrypt or decrypt the encrypted file data, and rotate the key as needed.
-fn crypt_file_data(key: &mut u32, counter: &mut u8, buffer: &mut [u8]) {
-    // TODO: We can possibly be more efficient here.
-    // If we are able to cast this to a slice of u32s,
-    // we can crypt that instead and use this byte-wise impl only at the end.
-    for byte in buffer.iter_mut() {
-        *byte ^= key.to_le_bytes()[usize::from(*counter)];
-        if *counter == 3 {
-            *key = key.overflowing_mul(7).0.overflowing_add(3).0;
-        }
-        *counter = (*counter + 1) % 4;
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -127,7 +186,7 @@ mod test {
         "test_data/RPGMakerVXTestGame-Export/RPGMakerVXTestGame/Game.rgss2a";
 
     #[derive(Debug, Clone)]
-    struct SlowReader<R> {
+    pub struct SlowReader<R> {
         inner: Rc<RefCell<(R, usize, Option<SeekFrom>)>>,
     }
 
@@ -138,7 +197,7 @@ mod test {
             }
         }
 
-        fn add_fuel(&self, fuel: usize) {
+        pub fn add_fuel(&self, fuel: usize) {
             let mut inner = self.inner.borrow_mut();
             inner.1 += fuel;
         }
@@ -189,6 +248,73 @@ mod test {
         }
     }
 
+    /// A reader that returns the first `limit` bytes of `data`, then always returns 0 bytes.
+    ///
+    /// Unlike a truncated cursor, seeking past `limit` is allowed,
+    /// and reads there still return 0 bytes.
+    #[derive(Debug)]
+    pub struct ZeroAfter {
+        data: std::io::Cursor<Vec<u8>>,
+        limit: u64,
+    }
+
+    impl ZeroAfter {
+        pub fn new(data: Vec<u8>, limit: usize) -> Self {
+            Self {
+                data: std::io::Cursor::new(data),
+                limit: u64::try_from(limit).unwrap(),
+            }
+        }
+
+        /// Limit a read of `len` bytes to the bytes before `limit`.
+        fn read_len(&self, len: usize) -> usize {
+            let available = self.limit.saturating_sub(self.data.position());
+            usize::try_from(available).map_or(len, |available| std::cmp::min(len, available))
+        }
+    }
+
+    impl Read for ZeroAfter {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.read_len(buf.len());
+            self.data.read(&mut buf[..len])
+        }
+    }
+
+    impl Seek for ZeroAfter {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.data.seek(pos)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl ::tokio::io::AsyncRead for ZeroAfter {
+        fn poll_read(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &mut ::tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            let len = self.read_len(buf.remaining());
+            let n = self.data.read(buf.initialize_unfilled_to(len))?;
+            buf.advance(n);
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl ::tokio::io::AsyncSeek for ZeroAfter {
+        fn start_seek(mut self: std::pin::Pin<&mut Self>, pos: SeekFrom) -> std::io::Result<()> {
+            self.data.seek(pos)?;
+            Ok(())
+        }
+
+        fn poll_complete(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<u64>> {
+            std::task::Poll::Ready(Ok(self.data.position()))
+        }
+    }
+
     #[derive(Debug, Clone)]
     struct SlowWriter<W> {
         inner: Rc<RefCell<(W, usize, bool)>>,
@@ -242,6 +368,10 @@ mod test {
         }
     }
 
+    /// Keep this in sync with the variants listed in the docs of [`Error`].
+    ///
+    /// Inside this crate, `#[non_exhaustive]` does not apply,
+    /// so this stops compiling when a variant is added.
     #[test]
     fn reader_writer_smoke() {
         let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
@@ -0,0 +1,166 @@
+/// Metadata for an archive entry, without its data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryInfo {
+    /// The file name.
+    pub name: String,
+
+    /// The file data size.
+    pub size: u32,
+}
+
+impl std::fmt::Display for EntryInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({} bytes)", self.name, self.size)
+    }
+}
+
+/// Render entries as a table with aligned name and size columns.
+///
+/// Sizes are human-readable, like `1.5 KiB`.
+/// Each row ends with a newline.
+pub fn render_table(entries: &[EntryInfo]) -> String {
+    const NAME_HEADER: &str = "Name";
+    const SIZE_HEADER: &str = "Size";
+
+    let sizes: Vec<String> = entries
+        .iter()
+        .map(|entry| format_size(entry.size))
+        .collect();
+
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.name.chars().count())
+        .chain(std::iter::once(NAME_HEADER.len()))
+        .max()
+        .unwrap_or(0);
+    let size_width = sizes
+        .iter()
+        .map(|size| size.len())
+        .chain(std::iter::once(SIZE_HEADER.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut table = String::new();
+    table.push_str(&format!(
+        "{NAME_HEADER:<name_width$}  {SIZE_HEADER:>size_width$}\n"
+    ));
+    for (entry, size) in entries.iter().zip(sizes.iter()) {
+        table.push_str(&format!(
+            "{:<name_width$}  {size:>size_width$}\n",
+            entry.name
+        ));
+    }
+    table
+}
+
+/// Format a size in bytes with a binary unit.
+fn format_size(size: u32) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if size < 1024 {
+        return format!("{size} B");
+    }
+
+    let mut value = f64::from(size);
+    let mut unit = "B";
+    for next_unit in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{value:.1} {unit}")
+}
+
+/// A group of archive entries with identical contents.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DuplicateGroup {
+    /// The names of the entries, in archive order.
+    pub names: Vec<String>,
+
+    /// The file data size of each entry.
+    pub size: u32,
+}
+
+impl DuplicateGroup {
+    /// Get the number of bytes that could be saved by only storing one copy of the contents.
+    pub fn wasted_bytes(&self) -> u64 {
+        // A group always has at least 2 entries.
+        let copies = u64::try_from(self.names.len()).unwrap() - 1;
+        u64::from(self.size) * copies
+    }
+}
+
+/// A snapshot of an archive's structure.
+///
+/// This records the archive version and the name and size of each entry, in archive order.
+/// With the `serde` feature, it can be saved and later compared against a repacked archive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    /// The archive format version.
+    pub version: u8,
+
+    /// The archive entries, in archive order.
+    pub entries: Vec<EntryInfo>,
+}
+
+impl Manifest {
+    /// Check if two manifests describe the same archive structure.
+    ///
+    /// This compares versions and entries, including entry order.
+    pub fn matches(&self, other: &Manifest) -> bool {
+        self == other
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entry_info_display() {
+        let entry = EntryInfo {
+            name: "Data\\Actors.rvdata".into(),
+            size: 1234,
+        };
+        assert!(entry.to_string() == "Data\\Actors.rvdata (1234 bytes)");
+    }
+
+    #[test]
+    fn render_table_aligns_columns() {
+        let entries = [
+            EntryInfo {
+                name: "a".into(),
+                size: 12,
+            },
+            EntryInfo {
+                name: "Data\\Actors.rvdata".into(),
+                size: 1536,
+            },
+            EntryInfo {
+                name: "Graphics\\Titles\\Title.png".into(),
+                size: 3 * 1024 * 1024,
+            },
+        ];
+
+        let table = render_table(&entries);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(
+            lines
+                == [
+                    "Name                          Size",
+                    "a                             12 B",
+                    "Data\\Actors.rvdata         1.5 KiB",
+                    "Graphics\\Titles\\Title.png  3.0 MiB",
+                ],
+            "{table}"
+        );
+        assert!(lines
+            .iter()
+            .all(|line| line.chars().count() == lines[0].chars().count()));
+    }
+}
@@ -0,0 +1,61 @@
+/// How file names are normalized when read from an archive.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NameNormalization {
+    /// File names are returned exactly as stored.
+    #[default]
+    None,
+
+    /// File names are cleaned up into relative, `/`-separated paths.
+    ///
+    /// Leading and trailing CR and LF characters are stripped,
+    /// both `\` and `/` are treated as separators,
+    /// repeated separators are collapsed,
+    /// and leading and trailing separators are removed.
+    /// `.` and `..` components are kept as-is.
+    Strict,
+}
+
+impl NameNormalization {
+    /// Normalize a file name.
+    pub fn normalize(self, name: String) -> String {
+        match self {
+            Self::None => name,
+            Self::Strict => name
+                .trim_matches(['\r', '\n'])
+                .split(['\\', '/'])
+                .filter(|component| !component.is_empty())
+                .collect::<Vec<_>>()
+                .join("/"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn strict() {
+        let cases = [
+            ("a\\\\b", "a/b"),
+            ("a/b/", "a/b"),
+            ("a\\b/c", "a/b/c"),
+            ("\\a\\b\r\n", "a/b"),
+            ("Data\\Actors.rvdata", "Data/Actors.rvdata"),
+        ];
+
+        for (input, expected) in cases {
+            let actual = NameNormalization::Strict.normalize(input.to_string());
+            assert!(
+                actual == expected,
+                "{input:?} -> {actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn none() {
+        let name = "a\\\\b/".to_string();
+        assert!(NameNormalization::None.normalize(name.clone()) == name);
+    }
+}
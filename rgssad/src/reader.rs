@@ -1,25 +1,111 @@
+use crate::crypt::Crypt;
+use crate::crypt::StandardCrypt;
+use crate::sans_io::FileHeader;
 use crate::sans_io::ReaderAction;
+use crate::DirNode;
+use crate::DuplicateGroup;
+use crate::EntryInfo;
 use crate::Error;
+use crate::Manifest;
+use crate::NameNormalization;
+use crate::RgssVersion;
+use std::collections::HashMap;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
+use std::path::Path;
+use std::sync::mpsc::SyncSender;
+
+/// A map of file names to the location of their data, from [`Reader::offset_map`].
+///
+/// Each value is the file data's offset from the start of the archive, its size, and the key at the start of the data.
+/// The key is always present for this archive version.
+pub type OffsetMap = HashMap<String, (u64, u32, Option<u32>)>;
 
 /// A reader for a "rgssad" archive file
 #[derive(Debug)]
-pub struct Reader<R> {
+pub struct Reader<R, C = StandardCrypt> {
     reader: R,
-    state_machine: crate::sans_io::Reader,
+    state_machine: crate::sans_io::Reader<C>,
+    name_normalization: NameNormalization,
+    engine_hint: Option<RgssVersion>,
+    progress: Option<SyncSender<ProgressEvent>>,
+    header_cache: Option<HeaderCache>,
 }
 
 impl<R> Reader<R> {
     /// Create a new [`Reader`] with the default encryption key.
     pub fn new(reader: R) -> Reader<R> {
+        Self::with_key(reader, crate::DEFAULT_KEY)
+    }
+
+    /// Create a new [`Reader`] with the given initial encryption key.
+    pub fn with_key(reader: R, key: u32) -> Reader<R> {
+        Self::with_crypt(reader, key, StandardCrypt)
+    }
+
+    /// Create a new [`Reader`] from a header that was already read from `reader`.
+    ///
+    /// This is useful when the header was read to detect the archive version.
+    /// `reader` must be positioned directly after the header,
+    /// and the header must have been at the start of `reader`,
+    /// as seek positions are relative to the start of the stream.
+    /// The header is validated by [`Reader::read_header`] as usual.
+    pub fn from_parts(reader: R, header: [u8; crate::HEADER_LEN]) -> Reader<R> {
+        Self::from_state_machine(reader, crate::sans_io::Reader::with_header(header))
+    }
+
+    /// Create a new [`Reader`] with the default encryption key and the given internal buffer capacity.
+    ///
+    /// See [`crate::sans_io::Reader::with_capacity`].
+    pub fn with_capacity(reader: R, capacity: usize) -> Reader<R> {
+        Self::from_state_machine(reader, crate::sans_io::Reader::with_capacity(capacity))
+    }
+}
+
+impl<R, C> Reader<R, C>
+where
+    C: Crypt,
+{
+    /// Create a new [`Reader`] with the given initial encryption key and encryption scheme.
+    ///
+    /// See [`Crypt`].
+    pub fn with_crypt(reader: R, key: u32, crypt: C) -> Reader<R, C> {
+        Self::from_state_machine(reader, crate::sans_io::Reader::with_crypt(key, crypt))
+    }
+
+    fn from_state_machine(reader: R, state_machine: crate::sans_io::Reader<C>) -> Reader<R, C> {
         Reader {
             reader,
-            state_machine: crate::sans_io::Reader::new(),
+            state_machine,
+            name_normalization: NameNormalization::None,
+            engine_hint: None,
+            progress: None,
+            header_cache: None,
         }
     }
 
+    /// Set how file names are normalized when files are read.
+    ///
+    /// By default, file names are returned exactly as stored.
+    pub fn set_name_normalization(&mut self, name_normalization: NameNormalization) {
+        self.name_normalization = name_normalization;
+    }
+
+    /// Get the RGSS version guessed from the archive's file extension.
+    ///
+    /// This is only known for readers made with [`Reader::open`].
+    pub fn engine_hint(&self) -> Option<RgssVersion> {
+        self.engine_hint
+    }
+
+    /// Get a mutable reference to this reader.
+    ///
+    /// Like [`Read::by_ref`], this allows passing this reader to functions that take it by value without moving it.
+    pub fn by_ref(&mut self) -> &mut Self {
+        self
+    }
+
     /// Get the inner reader.
     pub fn into_inner(self) -> R {
         self.reader
@@ -34,12 +120,181 @@ impl<R> Reader<R> {
     pub fn get_mut(&mut self) -> &mut R {
         &mut self.reader
     }
+
+    /// Set the maximum number of file data bytes that may be decrypted, across all files.
+    ///
+    /// See [`crate::sans_io::Reader::set_max_total_output`].
+    pub fn set_max_total_output(&mut self, max_total_output: Option<u64>) {
+        self.state_machine.set_max_total_output(max_total_output);
+    }
+
+    /// Set the maximum number of files that may be read.
+    ///
+    /// See [`crate::sans_io::Reader::set_max_entries`].
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.state_machine.set_max_entries(max_entries);
+    }
+
+    /// Set a channel to send [`ProgressEvent`]s to as files are read.
+    ///
+    /// Events are sent without blocking.
+    /// If the channel is full or disconnected, events are dropped,
+    /// so that a slow receiver never stalls reading.
+    /// By default, no events are sent.
+    pub fn set_progress_channel(&mut self, progress: Option<SyncSender<ProgressEvent>>) {
+        self.progress = progress;
+    }
+
+    /// Get the current encryption key.
+    ///
+    /// See [`crate::sans_io::Reader::key`].
+    pub fn key(&self) -> u32 {
+        self.state_machine.key()
+    }
+
+    /// Set the length of the archive, if known.
+    ///
+    /// See [`crate::sans_io::Reader::set_archive_len`].
+    pub fn set_archive_len(&mut self, archive_len: Option<u64>) {
+        self.state_machine.set_archive_len(archive_len);
+    }
+
+    /// Set whether the raw bytes of the header and each file header should be captured.
+    ///
+    /// See [`crate::sans_io::Reader::set_capture_raw_headers`].
+    pub fn set_capture_raw_headers(&mut self, capture_raw_headers: bool) {
+        self.state_machine
+            .set_capture_raw_headers(capture_raw_headers);
+    }
+
+    /// Get the raw header bytes captured so far.
+    ///
+    /// See [`crate::sans_io::Reader::raw_headers`].
+    pub fn raw_headers(&self) -> &[u8] {
+        self.state_machine.raw_headers()
+    }
+}
+
+impl Reader<std::io::BufReader<std::fs::File>> {
+    /// Open an archive file with the default encryption key.
+    ///
+    /// The file's extension is used to guess the RGSS version, available from [`Reader::engine_hint`].
+    /// Note that both "rgssad" and "rgss2a" archives use archive format version 1.
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        let mut reader = Self::new(std::io::BufReader::new(file));
+        reader.engine_hint = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(RgssVersion::from_extension);
+
+        Ok(reader)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Reader<std::io::Cursor<bytes::Bytes>> {
+    /// Create a new [`Reader`] over a [`bytes::Bytes`] buffer, with the default encryption key.
+    ///
+    /// The buffer is read in place, without copying it.
+    pub fn from_bytes_crate(bytes: bytes::Bytes) -> Self {
+        Self::new(std::io::Cursor::new(bytes))
+    }
 }
 
 impl<R> Reader<R>
 where
     R: Read + Seek,
 {
+    /// Create a new [`Reader`] for an archive that starts at `start_offset` in `reader`.
+    ///
+    /// This is useful for archives embedded in a larger file, like those appended to an executable.
+    /// `reader` is seeked to `start_offset` immediately.
+    /// File offsets are reported relative to the start of the archive.
+    pub fn new_at(mut reader: R, start_offset: u64) -> Result<Reader<R>, Error> {
+        reader.seek(SeekFrom::Start(start_offset))?;
+
+        let mut reader = Self::new(reader);
+        reader.state_machine.set_start_position(start_offset);
+
+        Ok(reader)
+    }
+}
+
+impl<R, C> Reader<R, C>
+where
+    R: Read + Seek,
+    C: Crypt,
+{
+    /// Get the inner reader, checking that the archive was completely read.
+    ///
+    /// This returns [`Error::NotFinished`] if there are unread files,
+    /// or if the current file's data was not completely read.
+    /// Trailing bytes after the last file cause an I/O error, as with [`Reader::read_file`].
+    /// On error, the inner reader is returned alongside the error.
+    pub fn try_into_inner(mut self) -> Result<R, (R, Error)> {
+        if self.state_machine.remaining_file_data() != 0 {
+            return Err((self.reader, Error::NotFinished));
+        }
+
+        match self.read_file() {
+            Ok(None) => Ok(self.reader),
+            Ok(Some(_file)) => Err((self.reader, Error::NotFinished)),
+            Err(error) => Err((self.reader, error)),
+        }
+    }
+
+    /// Guess the initial encryption key of an archive from a list of candidates.
+    ///
+    /// Each candidate is tried by decrypting the first file header.
+    /// The first candidate that produces a plausible file name,
+    /// made of printable ASCII characters, is returned.
+    /// This is a recovery aid for archives that do not use the default key.
+    /// Create a new reader with [`Reader::with_key`] to read the archive with the returned key.
+    ///
+    /// This does not change the state of this reader.
+    /// The inner reader's position is restored before this returns successfully.
+    pub fn try_keys(&mut self, candidates: &[u32]) -> Result<Option<u32>, Error> {
+        let position = self.reader.stream_position()?;
+        let start_position = self.state_machine.start_position;
+
+        let mut found = None;
+        for &key in candidates {
+            self.reader.seek(SeekFrom::Start(start_position))?;
+
+            let mut reader =
+                Reader::with_crypt(&mut self.reader, key, self.state_machine.crypt.clone());
+            reader.state_machine.set_start_position(start_position);
+            let is_plausible = match reader.read_file() {
+                Ok(Some(file)) => {
+                    !file.name().is_empty()
+                        && file
+                            .name()
+                            .bytes()
+                            .all(|byte| byte.is_ascii_graphic() || byte == b' ')
+                }
+                Ok(None) => false,
+                Err(Error::Io(error)) if error.kind() != std::io::ErrorKind::UnexpectedEof => {
+                    return Err(Error::Io(error));
+                }
+                Err(_error) => false,
+            };
+
+            if is_plausible {
+                found = Some(key);
+                break;
+            }
+        }
+
+        self.reader.seek(SeekFrom::Start(position))?;
+
+        Ok(found)
+    }
+
     /// Read and validate the header.
     ///
     /// After this returns, call [`Reader::read_file`] to read through entries.
@@ -50,6 +305,13 @@ where
                 ReaderAction::Read(size) => {
                     let space = self.state_machine.space();
                     let n = self.reader.read(&mut space[..size])?;
+                    if n == 0 {
+                        // The state machine only requests a read if the header is incomplete.
+                        return Err(Error::Io(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "archive ended before the header",
+                        )));
+                    }
                     self.state_machine.fill(n);
                 }
                 ReaderAction::Done(()) => return Ok(()),
@@ -59,7 +321,7 @@ where
     }
 
     /// Read the next file from this archive.
-    pub fn read_file(&mut self) -> Result<Option<File<R>>, Error> {
+    pub fn read_file(&mut self) -> Result<Option<File<'_, R, C>>, Error> {
         loop {
             match self.state_machine.step_read_file_header()? {
                 ReaderAction::Read(size) => {
@@ -84,32 +346,533 @@ where
                 }
                 ReaderAction::Done(file_header) => {
                     let size = file_header.size;
-                    return Ok(Some(File {
-                        name: file_header.name,
+                    let file = File {
+                        name: self.name_normalization.normalize(file_header.name),
                         size,
+                        offset: file_header.offset,
                         state_machine: &mut self.state_machine,
                         reader: &mut self.reader,
-                    }));
+                        progress: self.progress.as_ref(),
+                    };
+                    file.send_started();
+                    return Ok(Some(file));
+                }
+            }
+        }
+    }
+
+    /// Get the number of files in the archive.
+    ///
+    /// The first call scans every file header from the start of the archive and caches them,
+    /// so later calls do not rescan.
+    /// This does not change the state of this reader.
+    pub fn len(&mut self) -> Result<usize, Error> {
+        self.populate_header_cache()?;
+
+        // We populate the cache above.
+        Ok(self.header_cache.as_ref().unwrap().headers.len())
+    }
+
+    /// Returns true if the archive has no files.
+    ///
+    /// See [`Reader::len`].
+    pub fn is_empty(&mut self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Read the first file named `name`, seeking directly to its data.
+    ///
+    /// The first call scans every file header from the start of the archive and caches their locations,
+    /// so later calls only need to seek.
+    /// If multiple files have the same name, the first one is returned, matching [`Reader::offset_map`].
+    /// This returns `None` if there is no file with the name.
+    /// After the returned file, [`Reader::read_file`] continues with the file after it.
+    pub fn read_file_by_name(&mut self, name: &str) -> Result<Option<File<'_, R, C>>, Error> {
+        self.populate_header_cache()?;
+
+        // We populate the cache above.
+        let header_cache = self.header_cache.as_ref().unwrap();
+        let Some(&index) = header_cache.by_name.get(name) else {
+            return Ok(None);
+        };
+        let header = &header_cache.headers[index];
+
+        let position = self.state_machine.jump_to_file(header);
+        self.reader.seek(SeekFrom::Start(position))?;
+
+        let file = File {
+            name: header.name.clone(),
+            size: header.size,
+            offset: header.offset,
+            state_machine: &mut self.state_machine,
+            reader: &mut self.reader,
+            progress: self.progress.as_ref(),
+        };
+        file.send_started();
+        Ok(Some(file))
+    }
+
+    /// Scan the file headers from the start of the archive and cache them, if they are not cached already.
+    ///
+    /// The inner reader's position is restored after scanning.
+    fn populate_header_cache(&mut self) -> Result<(), Error> {
+        if self.header_cache.is_none() {
+            let position = self.reader.stream_position()?;
+            let start_position = self.state_machine.start_position;
+            self.reader.seek(SeekFrom::Start(start_position))?;
+
+            let mut reader = Reader::with_crypt(
+                &mut self.reader,
+                self.state_machine.initial_key,
+                self.state_machine.crypt.clone(),
+            );
+            reader.state_machine.set_start_position(start_position);
+            reader.set_name_normalization(self.name_normalization);
+            reader.set_max_entries(self.state_machine.max_entries);
+            reader.set_archive_len(self.state_machine.archive_len);
+            reader.set_max_total_output(self.state_machine.max_total_output);
+            let headers = reader.file_headers();
+
+            // Restore the position even if scanning failed, so this reader can still be used.
+            self.reader.seek(SeekFrom::Start(position))?;
+            let headers = headers?;
+
+            let mut by_name = HashMap::with_capacity(headers.len());
+            for (index, header) in headers.iter().enumerate() {
+                by_name.entry(header.name.clone()).or_insert(index);
+            }
+            self.header_cache = Some(HeaderCache { headers, by_name });
+        }
+
+        Ok(())
+    }
+
+    /// Call `f` with each remaining file, stopping at the first error.
+    ///
+    /// This is a callback instead of an [`Iterator`],
+    /// as each [`File`] mutably borrows this reader.
+    /// An iterator cannot yield items that borrow from itself,
+    /// so a file would have to be dropped before the next one was requested,
+    /// which [`Iterator`] cannot express.
+    /// Errors returned by `f` stop iteration and are returned.
+    pub fn for_each_file<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut File<'_, R, C>) -> Result<(), Error>,
+    {
+        while let Some(mut file) = self.read_file()? {
+            f(&mut file)?;
+        }
+        Ok(())
+    }
+
+    /// Skip to the next file named `name`, returning it.
+    ///
+    /// Files before it are discarded with [`File::skip_remaining`],
+    /// so this never seeks and works on sequential streams.
+    /// This returns `None` if the end of the archive is reached first.
+    pub fn skip_to(&mut self, name: &str) -> Result<Option<File<'_, R, C>>, Error> {
+        loop {
+            // Returning the file from inside the loop does not pass the borrow checker,
+            // so rebuild it once the matching file is found.
+            let (name, size, offset) = match self.read_file()? {
+                Some(mut file) => {
+                    if file.name() != name {
+                        file.skip_remaining()?;
+                        continue;
+                    }
+                    (file.name, file.size, file.offset)
+                }
+                None => return Ok(None),
+            };
+
+            return Ok(Some(File {
+                name,
+                size,
+                offset,
+                state_machine: &mut self.state_machine,
+                reader: &mut self.reader,
+                progress: self.progress.as_ref(),
+            }));
+        }
+    }
+
+    /// Read and validate the header, without treating [`std::io::ErrorKind::WouldBlock`] as an error.
+    ///
+    /// This is meant for non-blocking I/O.
+    /// If the inner reader would block, this returns [`StepResult::WouldBlock`],
+    /// and should be called again once the inner reader is ready.
+    pub fn step_read_header(&mut self) -> Result<StepResult<()>, Error> {
+        StepResult::from_result(self.read_header())
+    }
+
+    /// Read the next file from this archive, without treating [`std::io::ErrorKind::WouldBlock`] as an error.
+    ///
+    /// This is meant for non-blocking I/O.
+    /// If the inner reader would block, this returns [`StepResult::WouldBlock`],
+    /// and should be called again once the inner reader is ready.
+    /// Seeks are performed by this function,
+    /// so the inner reader's seeks may also return [`std::io::ErrorKind::WouldBlock`].
+    /// Reading the returned file's data will return [`std::io::ErrorKind::WouldBlock`] as usual for [`Read`].
+    pub fn step_read_file(&mut self) -> Result<StepResult<Option<File<'_, R, C>>>, Error> {
+        StepResult::from_result(self.read_file())
+    }
+
+    /// Scan the remaining file headers and group them into a directory tree.
+    ///
+    /// File data is skipped, not read.
+    /// After this returns, the reader is at the end of the archive.
+    pub fn directory_tree(&mut self) -> Result<DirNode, Error> {
+        let mut tree = DirNode::new();
+        while let Some(file) = self.read_file()? {
+            tree.insert(file.name(), file.size());
+        }
+        Ok(tree)
+    }
+
+    /// Scan the remaining file headers and build a [`Manifest`].
+    ///
+    /// File data is skipped, not read.
+    /// After this returns, the reader is at the end of the archive.
+    pub fn manifest(&mut self) -> Result<Manifest, Error> {
+        Ok(Manifest {
+            version: crate::VERSION,
+            entries: self.entries()?,
+        })
+    }
+
+    /// Scan the remaining file headers and get the name and size of each file.
+    ///
+    /// File data is skipped by seeking over it, so it is never read or decrypted.
+    /// After this returns, the reader is at the end of the archive,
+    /// and [`Reader::read_file`] returns `None`.
+    pub fn entries(&mut self) -> Result<Vec<EntryInfo>, Error> {
+        let mut entries = Vec::new();
+        while let Some(file) = self.read_file()? {
+            entries.push(EntryInfo {
+                name: file.name,
+                size: file.size,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Scan the remaining file headers and get the key at the start of each file's data.
+    ///
+    /// Together with a file's data offset and size,
+    /// this is enough to decrypt it with [`crate::crypt::crypt_entry_data`],
+    /// without reading the files before it.
+    /// File data is skipped, not read.
+    /// After this returns, the reader is at the end of the archive.
+    pub fn entry_keys(&mut self) -> Result<Vec<u32>, Error> {
+        let mut keys = Vec::new();
+        while let Some(file) = self.read_file()? {
+            keys.push(file.state_machine.key);
+        }
+        Ok(keys)
+    }
+
+    /// Scan the remaining file headers.
+    ///
+    /// Read the data of any returned header later with [`Reader::read_data_for`].
+    /// File names are normalized as usual.
+    /// File data is skipped, not read.
+    /// After this returns, the reader is at the end of the archive.
+    pub fn file_headers(&mut self) -> Result<Vec<FileHeader>, Error> {
+        let mut headers = Vec::new();
+        while let Some(file) = self.read_file()? {
+            headers.push(FileHeader {
+                key: file.state_machine.key,
+                name: file.name,
+                size: file.size,
+                offset: file.offset,
+            });
+        }
+        Ok(headers)
+    }
+
+    /// Read and decrypt the whole data of the file described by `header`.
+    ///
+    /// `header` should come from this archive, like from [`Reader::file_headers`].
+    /// This does not change the state of this reader,
+    /// and the inner reader's position is restored even if reading fails.
+    /// The size is checked against the archive length and total output limit, if set, before any data is read.
+    /// The data does not count towards the total output limit.
+    pub fn read_data_for(&mut self, header: &FileHeader) -> Result<Vec<u8>, Error> {
+        let end = header.offset.checked_add(u64::from(header.size));
+        if let Some(archive_len) = self.state_machine.archive_len {
+            if end.is_none_or(|end| end > archive_len) {
+                return Err(Error::SansIo(crate::sans_io::Error::InvalidFileSize {
+                    size: header.size,
+                }));
+            }
+        }
+        let size = usize::try_from(header.size)
+            .map_err(|_| Error::SansIo(crate::sans_io::Error::ValueTooLarge))?;
+        self.state_machine.check_output_limit(size)?;
+
+        let offset = self
+            .state_machine
+            .start_position
+            .checked_add(header.offset)
+            .ok_or(Error::SansIo(crate::sans_io::Error::ValueTooLarge))?;
+
+        let position = self.reader.stream_position()?;
+        let mut read_data = || {
+            self.reader.seek(SeekFrom::Start(offset))?;
+
+            // The size comes from the archive, so do not trust it for the allocation.
+            let mut data = Vec::new();
+            (&mut self.reader)
+                .take(u64::from(header.size))
+                .read_to_end(&mut data)?;
+            if data.len() != size {
+                return Err(Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                )));
+            }
+
+            let mut key = header.key;
+            self.state_machine
+                .crypt
+                .crypt_data(&mut key, &mut 0, &mut data);
+
+            Ok(data)
+        };
+        let data = read_data();
+
+        // Restore the position even if reading failed, so this reader can still be used.
+        self.reader.seek(SeekFrom::Start(position))?;
+
+        data
+    }
+
+    /// Scan the remaining file headers and map each file name to the location of its data.
+    ///
+    /// See [`OffsetMap`] for the meaning of each value.
+    /// With the archive in memory, or memory-mapped,
+    /// any file can then be decrypted in place with [`crate::crypt::crypt_entry_data`],
+    /// without going through a [`Reader`].
+    /// If multiple files have the same name, the first one wins, matching [`Reader::read_file_by_name`].
+    /// File data is skipped, not read.
+    /// After this returns, the reader is at the end of the archive.
+    pub fn offset_map(&mut self) -> Result<OffsetMap, Error> {
+        let mut map = HashMap::new();
+        while let Some(file) = self.read_file()? {
+            let value = (file.offset(), file.size(), Some(file.state_machine.key));
+            map.entry(file.name).or_insert(value);
+        }
+        Ok(map)
+    }
+
+    /// Feed the names and decrypted data of the remaining files through `hasher`, returning the hash.
+    ///
+    /// Unlike hashing the archive file itself,
+    /// this only depends on the logical contents of the archive, in order.
+    /// Archives with the same files, but different keys, produce the same hash.
+    /// The bytes fed to `hasher` are the same on every target,
+    /// so the hash only differs between targets if `hasher` itself does.
+    /// After this returns, the reader is at the end of the archive.
+    pub fn content_digest<H>(&mut self, mut hasher: H) -> Result<u64, Error>
+    where
+        H: std::hash::Hasher,
+    {
+        let mut buffer = vec![0; 10 * 1024];
+        while let Some(mut file) = self.read_file()? {
+            // Prefix variable-length fields with their lengths, so that entries cannot run together.
+            // Hash integers as little-endian bytes of a fixed size,
+            // so the hashed bytes are the same on every target.
+            let name_len = u64::try_from(file.name().len())
+                .map_err(|_| Error::SansIo(crate::sans_io::Error::ValueTooLarge))?;
+            hasher.write(&name_len.to_le_bytes());
+            hasher.write(file.name().as_bytes());
+            hasher.write(&file.size().to_le_bytes());
+
+            loop {
+                let n = file.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.write(&buffer[..n]);
+            }
+        }
+
+        Ok(hasher.finish())
+    }
+
+    /// Read the remaining files and group the ones with identical contents.
+    ///
+    /// Contents are compared by size and a 64-bit hash of the data.
+    /// Only groups of 2 or more entries are returned, in order of their first entry.
+    /// This is useful for finding assets that could be pruned before packing,
+    /// as the format cannot share data between entries.
+    /// After this returns, the reader is at the end of the archive.
+    pub fn duplicate_groups(&mut self) -> Result<Vec<DuplicateGroup>, Error> {
+        use std::hash::Hasher;
+
+        let mut groups: Vec<DuplicateGroup> = Vec::new();
+        let mut group_indices: HashMap<(u32, u64), usize> = HashMap::new();
+        let mut buffer = vec![0; 10 * 1024];
+        while let Some(mut file) = self.read_file()? {
+            let mut hasher = std::hash::DefaultHasher::new();
+            loop {
+                let n = file.read(&mut buffer)?;
+                if n == 0 {
+                    break;
                 }
+                hasher.write(&buffer[..n]);
+            }
+
+            let key = (file.size(), hasher.finish());
+            let index = *group_indices.entry(key).or_insert_with(|| {
+                groups.push(DuplicateGroup {
+                    names: Vec::new(),
+                    size: file.size,
+                });
+                groups.len() - 1
+            });
+            groups[index].names.push(file.name);
+        }
+
+        groups.retain(|group| group.names.len() > 1);
+
+        Ok(groups)
+    }
+
+    /// Write the remaining files into a tar archive.
+    ///
+    /// `\` separators in file names are replaced with `/`.
+    /// The tar archive is finished and flushed once all files are written.
+    #[cfg(feature = "tar")]
+    pub fn into_tar<W>(mut self, writer: W) -> Result<(), Error>
+    where
+        W: std::io::Write,
+    {
+        let mut builder = tar::Builder::new(writer);
+        while let Some(file) = self.read_file()? {
+            let path = file.name().replace('\\', "/");
+            let mut header = tar::Header::new_gnu();
+            header.set_size(u64::from(file.size()));
+            header.set_mode(0o644);
+            header.set_entry_type(tar::EntryType::Regular);
+            builder.append_data(&mut header, path, file)?;
+        }
+        let mut writer = builder.into_inner()?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Read the remaining files into memory, stopping at the first error.
+    ///
+    /// This returns the files read before the error, along with the error, if any.
+    /// This is useful for recovering what can be read from a damaged archive.
+    pub fn read_until_error(&mut self) -> (Vec<(String, Vec<u8>)>, Option<Error>) {
+        let mut files = Vec::new();
+        loop {
+            let mut file = match self.read_file() {
+                Ok(Some(file)) => file,
+                Ok(None) => return (files, None),
+                Err(error) => return (files, Some(error)),
+            };
+
+            let mut buffer = Vec::new();
+            if let Err(error) = file.read_to_end(&mut buffer) {
+                return (files, Some(Error::Io(error)));
+            }
+            files.push((file.name, buffer));
+        }
+    }
+}
+
+/// The file headers of an archive, scanned from the start.
+#[derive(Debug)]
+struct HeaderCache {
+    /// The file headers, in archive order.
+    headers: Vec<FileHeader>,
+
+    /// The index of the first header with each name.
+    by_name: HashMap<String, usize>,
+}
+
+/// A progress event sent by a [`Reader`], as set with [`Reader::set_progress_channel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// A file header was read.
+    Started {
+        /// The file name.
+        name: String,
+
+        /// The file data size.
+        size: u32,
+    },
+
+    /// Some file data was read.
+    Progress {
+        /// The file name.
+        name: String,
+
+        /// The total number of bytes of file data read so far.
+        bytes: u32,
+    },
+
+    /// All of a file's data was read.
+    ///
+    /// This is not sent for files that are skipped before all of their data is read.
+    Finished {
+        /// The file name.
+        name: String,
+    },
+}
+
+/// The result of a non-blocking [`Reader`] step.
+#[derive(Debug)]
+pub enum StepResult<T> {
+    /// The inner reader would block, so the step should be retried later.
+    WouldBlock,
+
+    /// The step finished.
+    Done(T),
+}
+
+impl<T> StepResult<T> {
+    /// Returns true if this is [`StepResult::Done`].
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Done(_))
+    }
+
+    fn from_result(result: Result<T, Error>) -> Result<Self, Error> {
+        match result {
+            Ok(value) => Ok(Self::Done(value)),
+            Err(Error::Io(error)) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                Ok(Self::WouldBlock)
             }
+            Err(error) => Err(error),
         }
     }
 }
 
 /// An file in an rgssad file
 #[derive(Debug)]
-pub struct File<'a, R> {
+pub struct File<'a, R, C = StandardCrypt> {
     /// The file path.
     name: String,
 
     /// The file size.
     size: u32,
 
+    /// The offset of the file data.
+    offset: u64,
+
     reader: &'a mut R,
-    state_machine: &'a mut crate::sans_io::Reader,
+    state_machine: &'a mut crate::sans_io::Reader<C>,
+    progress: Option<&'a SyncSender<ProgressEvent>>,
 }
 
-impl<R> File<'_, R> {
+impl<R, C> File<'_, R, C>
+where
+    C: Crypt,
+{
     /// The file path
     pub fn name(&self) -> &str {
         self.name.as_str()
@@ -119,42 +882,201 @@ impl<R> File<'_, R> {
     pub fn size(&self) -> u32 {
         self.size
     }
+
+    /// The offset of the file data from the start of the archive.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The number of bytes of file data left to read.
+    pub fn remaining(&self) -> u32 {
+        self.state_machine.remaining_file_data()
+    }
+
+    /// Returns true if this file has no data.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Send a progress event, if a progress channel was set.
+    fn send_progress(&self, event: ProgressEvent) {
+        if let Some(progress) = self.progress {
+            // Dropping events is better than stalling the reader.
+            let _ = progress.try_send(event);
+        }
+    }
+
+    /// Send the event for starting this file.
+    ///
+    /// Empty files have no data to read, so they are finished immediately.
+    fn send_started(&self) {
+        self.send_progress(ProgressEvent::Started {
+            name: self.name.clone(),
+            size: self.size,
+        });
+        if self.size == 0 {
+            self.send_progress(ProgressEvent::Finished {
+                name: self.name.clone(),
+            });
+        }
+    }
 }
 
-impl<R> Read for File<'_, R>
+impl<R, C> File<'_, R, C>
 where
     R: Read,
+    C: Crypt,
 {
-    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+    /// Read and discard the rest of this file's data, returning the number of bytes skipped.
+    ///
+    /// The data is not decrypted, so this is cheaper than copying into [`std::io::sink`].
+    /// Unlike skipping by reading the next file, this never seeks,
+    /// so the next file may be read without seeking.
+    pub fn skip_remaining(&mut self) -> Result<u64, Error> {
+        let mut skipped = 0;
         loop {
-            let action = self
-                .state_machine
-                .step_read_file_data(buffer)
-                .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
-
-            match action {
+            match self.state_machine.step_skip_file_data()? {
                 ReaderAction::Read(size) => {
                     let space = self.state_machine.space();
-
-                    // Even if we read shorter than requested,
-                    // the state machine is tolerant to this
-                    // and will request another read if needed.
                     let n = self.reader.read(&mut space[..size])?;
+                    if n == 0 {
+                        // The state machine only requests a read if file data remains.
+                        return Err(Error::Io(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "archive ended before the file data",
+                        )));
+                    }
                     self.state_machine.fill(n);
                 }
                 ReaderAction::Seek(_) => unreachable!(),
-                ReaderAction::Done(n) => return Ok(n),
+                ReaderAction::Done(0) => return Ok(skipped),
+                ReaderAction::Done(n) => {
+                    // A usize always fits in a u64 on supported platforms.
+                    skipped += u64::try_from(n).unwrap();
+                }
             }
         }
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::test::*;
+impl<R, C> Read for File<'_, R, C>
+where
+    R: Read,
+    C: Crypt,
+{
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.read_data(buffer)?;
 
-    #[test]
+        if n != 0 && self.progress.is_some() {
+            let remaining = self.state_machine.remaining_file_data();
+            self.send_progress(ProgressEvent::Progress {
+                name: self.name.clone(),
+                bytes: self.size - remaining,
+            });
+            if remaining == 0 {
+                self.send_progress(ProgressEvent::Finished {
+                    name: self.name.clone(),
+                });
+            }
+        }
+
+        Ok(n)
+    }
+}
+
+impl<R, C> File<'_, R, C>
+where
+    R: Read,
+    C: Crypt,
+{
+    /// Read and decrypt file data into `buffer`.
+    fn read_data(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        // For reads at least as large as the internal buffer,
+        // read straight into the caller's buffer and decrypt in place to skip a copy.
+        let remaining = self.state_machine.remaining_file_data();
+        if remaining != 0
+            && self.state_machine.available_data() == 0
+            && buffer.len() >= self.state_machine.capacity()
+        {
+            // If the remaining bytes cannot fit in a usize, they are larger than any buffer.
+            let remaining_usize = usize::try_from(remaining).unwrap_or(usize::MAX);
+            let len = std::cmp::min(buffer.len(), remaining_usize);
+
+            // Bytes read from the inner reader cannot be put back,
+            // so check the output limit before reading instead of after.
+            self.state_machine
+                .check_output_limit(len)
+                .map_err(std::io::Error::other)?;
+            let buffer = &mut buffer[..len];
+
+            let n = self.reader.read(buffer)?;
+            if n == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "archive ended before the file data",
+                ));
+            }
+            self.state_machine
+                .decrypt_file_data(&mut buffer[..n])
+                .map_err(std::io::Error::other)?;
+
+            return Ok(n);
+        }
+
+        loop {
+            let action = self
+                .state_machine
+                .step_read_file_data(buffer)
+                .map_err(std::io::Error::other)?;
+
+            match action {
+                ReaderAction::Read(size) => {
+                    let space = self.state_machine.space();
+
+                    // Even if we read shorter than requested,
+                    // the state machine is tolerant to this
+                    // and will request another read if needed.
+                    let n = self.reader.read(&mut space[..size])?;
+                    if n == 0 {
+                        // The state machine only requests a read if file data remains.
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "archive ended before the file data",
+                        ));
+                    }
+                    self.state_machine.fill(n);
+                }
+                ReaderAction::Seek(_) => unreachable!(),
+                ReaderAction::Done(n) => return Ok(n),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+
+    /// A reader that panics if it is asked to seek.
+    struct NoSeek<R>(R);
+
+    impl<R> Read for NoSeek<R>
+    where
+        R: Read,
+    {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl<R> Seek for NoSeek<R> {
+        fn seek(&mut self, _pos: SeekFrom) -> std::io::Result<u64> {
+            panic!("reader should not seek");
+        }
+    }
+
+    #[test]
     fn reader_smoke() {
         let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
         let file = std::io::Cursor::new(file);
@@ -185,6 +1107,1261 @@ mod test {
         assert!(files.len() == num_skipped_entries);
     }
 
+    #[test]
+    fn reader_step_would_block() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let num_entries = Reader::new(std::io::Cursor::new(file.clone()))
+            .manifest()
+            .expect("failed to build manifest")
+            .entries
+            .len();
+
+        let file = SlowReader::new(std::io::Cursor::new(file));
+        let mut reader = Reader::new(file.clone());
+
+        while !reader
+            .step_read_header()
+            .expect("failed to read header")
+            .is_done()
+        {
+            file.add_fuel(1);
+        }
+
+        let mut num_read_entries = 0;
+        loop {
+            match reader.step_read_file().expect("failed to read file") {
+                StepResult::WouldBlock => file.add_fuel(1),
+                StepResult::Done(Some(_file)) => num_read_entries += 1,
+                StepResult::Done(None) => break,
+            }
+        }
+        assert!(num_read_entries == num_entries);
+    }
+
+    #[test]
+    fn reader_directory_tree() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let file = std::io::Cursor::new(file);
+        let mut reader = Reader::new(file);
+        reader.read_header().expect("failed to read header");
+
+        let tree = reader
+            .directory_tree()
+            .expect("failed to build directory tree");
+
+        // Compare against a flat scan.
+        let mut file = reader.into_inner();
+        file.seek(SeekFrom::Start(0))
+            .expect("failed to seek to start");
+        let mut reader = Reader::new(file);
+        let mut num_entries = 0;
+        let mut size = 0;
+        let mut num_data_entries = 0;
+        while let Some(file) = reader.read_file().expect("failed to read file") {
+            num_entries += 1;
+            size += u64::from(file.size());
+            if file.name().starts_with("Data\\") {
+                num_data_entries += 1;
+            }
+        }
+
+        assert!(tree.num_entries == num_entries);
+        assert!(tree.size == size);
+
+        let data = tree.get_dir("Data").expect("missing \"Data\" dir");
+        assert!(data.num_entries == num_data_entries);
+        assert!(data.files.len() == num_data_entries);
+        assert!(
+            data.files
+                .values()
+                .map(|size| u64::from(*size))
+                .sum::<u64>()
+                == data.size
+        );
+    }
+
+    #[test]
+    fn reader_key_fast_forward() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let file = std::io::Cursor::new(file);
+        let mut reader = Reader::new(file);
+        reader.read_header().expect("failed to read header");
+
+        let mut key = crate::DEFAULT_KEY;
+        while let Some(file) = reader.read_file().expect("failed to read file") {
+            crate::crypt::advance_key_over(&mut key, file.name().len());
+            assert!(key == file.state_machine.key);
+        }
+    }
+
+    #[test]
+    fn reader_entry_keys() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        let keys = Reader::new(std::io::Cursor::new(file.clone()))
+            .entry_keys()
+            .expect("failed to get entry keys");
+        let manifest = Reader::new(std::io::Cursor::new(file.clone()))
+            .manifest()
+            .expect("failed to build manifest");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+        assert!(keys.len() == manifest.entries.len());
+
+        // Decrypt the third file directly from its offset and key.
+        let index = 2;
+        let offset: usize = crate::HEADER_LEN
+            + manifest.entries[..index]
+                .iter()
+                .map(|entry| 8 + entry.name.len() + usize::try_from(entry.size).unwrap())
+                .sum::<usize>()
+            + 8
+            + manifest.entries[index].name.len();
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        for _ in 0..index {
+            reader.read_file().expect("failed to read file");
+        }
+        let third_file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(third_file.offset() == u64::try_from(offset).unwrap());
+        let size = usize::try_from(manifest.entries[index].size).unwrap();
+        let mut data = file[offset..offset + size].to_vec();
+        crate::crypt::crypt_entry_data(keys[index], &mut data);
+
+        assert!(data == files[index].1);
+    }
+
+    #[test]
+    fn reader_offset_map() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let map = Reader::new(std::io::Cursor::new(file.clone()))
+            .offset_map()
+            .expect("failed to build offset map");
+        assert!(map.len() == files.len());
+
+        for (file_name, file_data) in files.iter() {
+            let (offset, size, key) = map[file_name];
+            let offset = usize::try_from(offset).unwrap();
+            let size = usize::try_from(size).unwrap();
+            let key = key.expect("missing key");
+
+            let mut data = file[offset..offset + size].to_vec();
+            crate::crypt::crypt_entry_data(key, &mut data);
+            assert!(&data == file_data);
+        }
+
+        // Duplicate names resolve to the same file as read_file_by_name.
+        let mut writer = crate::Writer::new_vec();
+        for data in [b"first", b"later"] {
+            writer
+                .write_file("a", 5, &data[..])
+                .expect("failed to write file");
+        }
+        let archive = writer.into_vec().expect("failed to finish");
+        let map = Reader::new(std::io::Cursor::new(archive.clone()))
+            .offset_map()
+            .expect("failed to build offset map");
+        let mut reader = Reader::new(std::io::Cursor::new(archive));
+        reader.read_header().expect("failed to read header");
+        let file = reader
+            .read_file_by_name("a")
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(map["a"].0 == file.offset());
+    }
+
+    #[test]
+    fn reader_manifest() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let manifest = reader.manifest().expect("failed to build manifest");
+        assert!(manifest.version == 1);
+        assert!(!manifest.entries.is_empty());
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let mut other = reader.manifest().expect("failed to build manifest");
+        assert!(manifest.matches(&other));
+
+        other.entries.swap(0, 1);
+        assert!(!manifest.matches(&other));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reader_manifest_serde() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let manifest = reader.manifest().expect("failed to build manifest");
+
+        let json = serde_json::to_string(&manifest).expect("failed to serialize manifest");
+        let deserialized: Manifest =
+            serde_json::from_str(&json).expect("failed to deserialize manifest");
+        assert!(manifest.matches(&deserialized));
+    }
+
+    #[test]
+    fn reader_from_parts() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let expected = reader.manifest().expect("failed to build manifest");
+
+        let mut file = std::io::Cursor::new(file);
+        let mut header = [0; crate::HEADER_LEN];
+        file.read_exact(&mut header).expect("failed to read header");
+
+        let mut reader = Reader::from_parts(file, header);
+        reader.read_header().expect("failed to read header");
+        let actual = reader.manifest().expect("failed to build manifest");
+
+        assert!(expected.matches(&actual));
+
+        let mut header = [0; crate::HEADER_LEN];
+        header[..3].copy_from_slice(b"bad");
+        let mut reader = Reader::from_parts(std::io::Cursor::new(Vec::new()), header);
+        let error = reader.read_header().expect_err("header should be invalid");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::InvalidMagic { .. })
+        ));
+    }
+
+    #[test]
+    fn reader_name_normalization() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.set_name_normalization(NameNormalization::Strict);
+
+        let manifest = reader.manifest().expect("failed to build manifest");
+        assert!(manifest
+            .entries
+            .iter()
+            .all(|entry| !entry.name.contains('\\')));
+    }
+
+    #[test]
+    fn reader_max_total_output() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let manifest = reader.manifest().expect("failed to build manifest");
+        let total: u64 = manifest
+            .entries
+            .iter()
+            .map(|entry| u64::from(entry.size))
+            .sum();
+
+        let read_all = |limit| -> std::io::Result<()> {
+            let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+            reader.set_max_total_output(Some(limit));
+            while let Some(mut file) = reader.read_file().expect("failed to read file") {
+                std::io::copy(&mut file, &mut std::io::sink())?;
+            }
+            Ok(())
+        };
+
+        read_all(total).expect("reading up to the limit should succeed");
+
+        let error = read_all(total - 1).expect_err("limit should have been exceeded");
+        let error = error
+            .into_inner()
+            .expect("missing inner error")
+            .downcast::<crate::sans_io::Error>()
+            .expect("wrong inner error type");
+        assert!(matches!(
+            *error,
+            crate::sans_io::Error::OutputLimitExceeded { limit } if limit == total - 1
+        ));
+    }
+
+    #[test]
+    fn reader_open() {
+        let mut reader = Reader::open(VX_TEST_GAME).expect("failed to open archive");
+        assert!(reader.engine_hint() == Some(RgssVersion::Rgss2));
+        reader.read_header().expect("failed to read header");
+        assert!(reader.read_file().expect("failed to read file").is_some());
+
+        let reader = Reader::new(std::io::Cursor::new(Vec::<u8>::new()));
+        assert!(reader.engine_hint().is_none());
+    }
+
+    #[test]
+    fn reader_truncated_file_data() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        // Find the end of the first file header, then cut the file data short.
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let manifest = reader.manifest().expect("failed to build manifest");
+        let first_entry = &manifest.entries[0];
+        assert!(first_entry.size > 1);
+        let header_end = crate::HEADER_LEN + 8 + first_entry.name.len();
+        let data_len = usize::try_from(first_entry.size).unwrap();
+        let file = file[..header_end + data_len / 2].to_vec();
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let mut first_file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        let mut buffer = Vec::new();
+        let error = first_file
+            .read_to_end(&mut buffer)
+            .expect_err("file data should be truncated");
+        assert!(error.kind() == std::io::ErrorKind::UnexpectedEof);
+        assert!(buffer.len() == data_len / 2);
+    }
+
+    #[test]
+    fn reader_read_until_error() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        let manifest = Reader::new(std::io::Cursor::new(file.clone()))
+            .manifest()
+            .expect("failed to build manifest");
+        assert!(files.len() == manifest.entries.len());
+
+        // Cut the archive partway through the third file header.
+        let second_entry_end: usize = crate::HEADER_LEN
+            + manifest.entries[..2]
+                .iter()
+                .map(|entry| 8 + entry.name.len() + usize::try_from(entry.size).unwrap())
+                .sum::<usize>();
+        let file = file[..second_entry_end + 2].to_vec();
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let (truncated_files, error) = reader.read_until_error();
+        assert!(truncated_files.len() == 2);
+        assert!(truncated_files[..] == files[..2]);
+        assert!(matches!(
+            error,
+            Some(Error::Io(error)) if error.kind() == std::io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn reader_skip_remaining() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        // Skip the second file, reading the rest.
+        let mut reader = Reader::new(NoSeek(std::io::Cursor::new(file)));
+        let mut index = 0;
+        while let Some(mut file) = reader.read_file().expect("failed to read file") {
+            if index == 1 {
+                let skipped = file.skip_remaining().expect("failed to skip file");
+                assert!(skipped == u64::from(file.size()));
+                assert!(file.remaining() == 0);
+            } else {
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer).expect("failed to read file");
+                assert!(buffer == files[index].1);
+            }
+
+            index += 1;
+        }
+        assert!(index == files.len());
+    }
+
+    #[test]
+    fn reader_for_each_file() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let mut actual = Vec::new();
+        Reader::new(std::io::Cursor::new(file.clone()))
+            .for_each_file(|file| {
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer)?;
+                actual.push((file.name().to_string(), buffer));
+                Ok(())
+            })
+            .expect("failed to read files");
+        assert!(actual == files);
+
+        // Errors from the callback stop iteration.
+        let mut num_files = 0;
+        let error = Reader::new(std::io::Cursor::new(file))
+            .for_each_file(|_file| {
+                num_files += 1;
+                Err(Error::InvalidState)
+            })
+            .expect_err("callback error should be returned");
+        assert!(matches!(error, Error::InvalidState));
+        assert!(num_files == 1);
+    }
+
+    #[test]
+    fn reader_entries() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.set_max_total_output(Some(0));
+        let entries = reader.entries().expect("failed to list entries");
+        assert!(entries.len() == files.len());
+        for (entry, (name, data)) in entries.iter().zip(files.iter()) {
+            assert!(entry.name == *name);
+            assert!(usize::try_from(entry.size).unwrap() == data.len());
+        }
+
+        // No file data was decrypted, and the reader is at the end.
+        assert!(reader.read_file().expect("failed to read file").is_none());
+    }
+
+    #[test]
+    fn reader_read_file_by_name() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        // Start partway through, to make sure earlier files are still found.
+        reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        for index in [5, 0, files.len() - 1, 2] {
+            let (name, data) = &files[index];
+            let mut file = reader
+                .read_file_by_name(name)
+                .expect("failed to read file")
+                .expect("missing file");
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer).expect("failed to read file");
+            assert!(buffer == *data, "{name}");
+        }
+
+        // Reading continues after the last file read by name.
+        let mut next = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(next.name() == files[3].0);
+        let mut buffer = Vec::new();
+        next.read_to_end(&mut buffer).expect("failed to read file");
+        assert!(buffer == files[3].1);
+
+        assert!(reader
+            .read_file_by_name("missing")
+            .expect("failed to read file")
+            .is_none());
+
+        // The first of duplicate names is returned.
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("a", 5, &b"first"[..])
+            .expect("failed to write file");
+        writer
+            .write_file("a", 6, &b"second"[..])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+        let mut reader = Reader::new(std::io::Cursor::new(archive));
+        let mut file = reader
+            .read_file_by_name("a")
+            .expect("failed to read file")
+            .expect("missing file");
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).expect("failed to read file");
+        assert!(buffer == b"first");
+    }
+
+    #[test]
+    fn reader_read_file_by_name_max_entries() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.set_max_entries(Some(3));
+        reader.read_header().expect("failed to read header");
+
+        // The scan for the header index is capped too, even if the file is before the limit.
+        let error = reader
+            .read_file_by_name("Data\\Actors.rvdata")
+            .expect_err("scan should hit the entry limit");
+        assert!(
+            matches!(
+                error,
+                Error::SansIo(crate::sans_io::Error::TooManyEntries { max: 3 })
+            ),
+            "{error:?}"
+        );
+
+        // The failed scan does not disturb normal reading.
+        let file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(file.name() == "Data\\Actors.rvdata");
+    }
+
+    #[test]
+    fn reader_len() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        // Start partway through, to make sure earlier files are still counted.
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(reader.len().expect("failed to count files") == files.len());
+        assert!(!reader.is_empty().expect("failed to count files"));
+
+        // The count is cached, so the archive is not scanned again.
+        reader.get_mut().get_mut().clear();
+        assert!(reader.len().expect("failed to count files") == files.len());
+
+        let archive = crate::Writer::new_vec()
+            .into_vec()
+            .expect("failed to finish");
+        let mut reader = Reader::new(std::io::Cursor::new(archive));
+        assert!(reader.len().expect("failed to count files") == 0);
+        assert!(reader.is_empty().expect("failed to count files"));
+    }
+
+    #[test]
+    fn reader_len_max_entries() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.set_max_entries(Some(3));
+
+        let error = reader.len().expect_err("count should hit the entry limit");
+        assert!(
+            matches!(
+                error,
+                Error::SansIo(crate::sans_io::Error::TooManyEntries { max: 3 })
+            ),
+            "{error:?}"
+        );
+        assert!(reader.is_empty().is_err());
+    }
+
+    #[test]
+    fn reader_skip_to() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let mut reader = Reader::new(NoSeek(std::io::Cursor::new(file)));
+        let mut third = reader
+            .skip_to(&files[2].0)
+            .expect("failed to skip")
+            .expect("missing file");
+        assert!(third.name() == files[2].0);
+        let mut buffer = Vec::new();
+        third.read_to_end(&mut buffer).expect("failed to read file");
+        assert!(buffer == files[2].1);
+
+        // The next file can still be read.
+        let mut fourth = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        let mut buffer = Vec::new();
+        fourth
+            .read_to_end(&mut buffer)
+            .expect("failed to read file");
+        assert!(buffer == files[3].1);
+
+        assert!(reader.skip_to("missing").expect("failed to skip").is_none());
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn reader_into_tar() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let mut tar_file = Vec::new();
+        Reader::new(std::io::Cursor::new(file))
+            .into_tar(&mut tar_file)
+            .expect("failed to write tar");
+
+        let mut tar_reader = tar::Archive::new(&*tar_file);
+        let mut num_entries = 0;
+        for (entry, (file_name, file_data)) in tar_reader
+            .entries()
+            .expect("failed to read tar")
+            .zip(files.iter())
+        {
+            let mut entry = entry.expect("failed to read tar entry");
+            let path = entry.path().expect("invalid tar path").into_owned();
+            assert!(path.to_str() == Some(&*file_name.replace('\\', "/")));
+
+            let mut buffer = Vec::new();
+            entry
+                .read_to_end(&mut buffer)
+                .expect("failed to read tar entry");
+            assert!(&buffer == file_data);
+
+            num_entries += 1;
+        }
+        assert!(num_entries == files.len());
+    }
+
+    #[test]
+    fn reader_new_at() {
+        let archive = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) = Reader::new(std::io::Cursor::new(archive.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let junk = b"MZ this is not an archive";
+        let mut embedded = junk.to_vec();
+        embedded.extend(&archive);
+
+        let start_offset = u64::try_from(junk.len()).unwrap();
+        let mut reader = Reader::new_at(std::io::Cursor::new(embedded.clone()), start_offset)
+            .expect("failed to seek");
+        reader.read_header().expect("failed to read header");
+
+        // Skip every other file, to exercise seeking.
+        let mut index = 0;
+        while let Some(mut file) = reader.read_file().expect("failed to read file") {
+            if index % 2 == 1 {
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer).expect("failed to read file");
+                assert!(buffer == files[index].1);
+
+                // Offsets are relative to the start of the archive.
+                let offset = usize::try_from(file.offset()).unwrap();
+                let start = junk.len() + offset;
+                assert!(
+                    embedded[start..start + buffer.len()] == archive[offset..offset + buffer.len()]
+                );
+            }
+            index += 1;
+        }
+        assert!(index == files.len());
+    }
+
+    #[test]
+    fn reader_try_keys() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+
+        let candidates = [0, 1, 0xCAFEDEAD, 0x12345678, crate::DEFAULT_KEY, 0xFFFFFFFF];
+        let key = reader
+            .try_keys(&candidates)
+            .expect("failed to try keys")
+            .expect("no key was found");
+        assert!(key == crate::DEFAULT_KEY);
+
+        let key = reader
+            .try_keys(&[0, 1, 0xCAFEDEAD])
+            .expect("failed to try keys");
+        assert!(key.is_none());
+
+        // The reader is unaffected.
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        assert!(!files.is_empty());
+    }
+
+    #[test]
+    fn reader_by_ref() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let (files, error) = reader.by_ref().read_until_error();
+        assert!(error.is_none());
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let mut file = reader
+            .by_ref()
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(file.size() > 100);
+
+        // Read a prefix, then the rest.
+        let mut prefix = Vec::new();
+        file.by_ref()
+            .take(100)
+            .read_to_end(&mut prefix)
+            .expect("failed to read file");
+        assert!(prefix[..] == files[0].1[..100]);
+
+        let mut rest = Vec::new();
+        file.read_to_end(&mut rest).expect("failed to read file");
+        assert!(rest[..] == files[0].1[100..]);
+    }
+
+    #[test]
+    fn reader_read_data_for() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let headers = reader.file_headers().expect("failed to scan headers");
+        assert!(headers.len() == files.len());
+
+        // Read out of order, to make sure each read is independent.
+        for (header, (name, data)) in headers.iter().zip(files.iter()).rev() {
+            assert!(header.name == *name);
+            let actual = reader
+                .read_data_for(header)
+                .expect("failed to read file data");
+            assert!(actual == *data);
+        }
+
+        // The reader is still at the end of the archive.
+        assert!(reader.read_file().expect("failed to read file").is_none());
+
+        // Scanning again gives the same headers.
+        let file = reader.into_inner().into_inner();
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let rescanned = reader.file_headers().expect("failed to scan headers");
+        assert!(rescanned == headers);
+    }
+
+    #[test]
+    fn reader_read_data_for_bad_size() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let file_len = u64::try_from(file.len()).unwrap();
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        let headers = reader.file_headers().expect("failed to scan headers");
+        let mut file = reader.into_inner();
+        file.set_position(0);
+        let mut reader = Reader::new(file);
+        reader.read_header().expect("failed to read header");
+
+        // A size that runs past the end of the archive fails without allocating the whole size.
+        let mut header = headers[0].clone();
+        header.size = u32::MAX;
+        let error = reader
+            .read_data_for(&header)
+            .expect_err("read past the end of the archive");
+        assert!(
+            matches!(error, Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+
+        reader.set_archive_len(Some(file_len));
+        let error = reader
+            .read_data_for(&header)
+            .expect_err("read past the end of the archive");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::InvalidFileSize { .. })
+        ));
+
+        reader.set_max_total_output(Some(u64::from(headers[0].size) - 1));
+        let error = reader
+            .read_data_for(&headers[0])
+            .expect_err("read past the output limit");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::OutputLimitExceeded { .. })
+        ));
+        reader.set_max_total_output(None);
+
+        // The failed reads did not move the reader.
+        let mut file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(file.name() == headers[0].name);
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).expect("failed to read file");
+        assert!(
+            data == reader
+                .read_data_for(&headers[0])
+                .expect("failed to read data")
+        );
+    }
+
+    #[test]
+    fn reader_content_digest() {
+        use std::hash::DefaultHasher;
+
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+
+        let mut writer = crate::Writer::with_key(Vec::new(), 0xDEADBEEF);
+        for (file_name, file_data) in files.iter() {
+            let len = u32::try_from(file_data.len()).expect("file data too large");
+            writer
+                .write_file(file_name, len, &**file_data)
+                .expect("failed to write file");
+        }
+        writer.finish().expect("failed to flush");
+        let repacked = writer.into_inner();
+        assert!(repacked != file);
+
+        let expected = Reader::new(std::io::Cursor::new(file))
+            .content_digest(DefaultHasher::new())
+            .expect("failed to hash archive");
+        let actual = Reader::with_key(std::io::Cursor::new(repacked), 0xDEADBEEF)
+            .content_digest(DefaultHasher::new())
+            .expect("failed to hash archive");
+        assert!(actual == expected);
+
+        // Changing the contents changes the hash.
+        let mut writer = crate::Writer::new_vec();
+        for (file_name, file_data) in files.iter().skip(1) {
+            let len = u32::try_from(file_data.len()).expect("file data too large");
+            writer
+                .write_file(file_name, len, &**file_data)
+                .expect("failed to write file");
+        }
+        let changed = writer.into_vec().expect("failed to finish");
+        let actual = Reader::new(std::io::Cursor::new(changed))
+            .content_digest(DefaultHasher::new())
+            .expect("failed to hash archive");
+        assert!(actual != expected);
+
+        // The hashed bytes do not depend on the size of a usize or the byte order of the target.
+        #[derive(Default)]
+        struct RecordingHasher(Vec<u8>);
+
+        impl std::hash::Hasher for RecordingHasher {
+            fn finish(&self) -> u64 {
+                0
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                self.0.extend_from_slice(bytes);
+            }
+        }
+
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("a", 1, &b"b"[..])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+        let mut hasher = RecordingHasher::default();
+        Reader::new(std::io::Cursor::new(archive))
+            .content_digest(&mut hasher)
+            .expect("failed to hash archive");
+        let mut expected = 1_u64.to_le_bytes().to_vec();
+        expected.extend_from_slice(b"a");
+        expected.extend_from_slice(&1_u32.to_le_bytes());
+        expected.extend_from_slice(b"b");
+        assert!(hasher.0 == expected);
+    }
+
+    #[test]
+    fn reader_capture_raw_headers() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        reader.set_capture_raw_headers(true);
+
+        reader.read_header().expect("failed to read header");
+        assert!(reader.raw_headers() == &file[..crate::HEADER_LEN]);
+
+        let mut first = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        let offset = usize::try_from(first.offset()).unwrap();
+        let mut data = Vec::new();
+        first.read_to_end(&mut data).expect("failed to read file");
+        assert!(reader.raw_headers() == &file[..offset]);
+
+        // Decoding is not affected.
+        let mut expected = Reader::new(std::io::Cursor::new(file));
+        let (files, error) = expected.read_until_error();
+        assert!(error.is_none());
+        assert!(data == files[0].1);
+
+        reader.set_capture_raw_headers(false);
+        assert!(reader.raw_headers().is_empty());
+    }
+
+    #[test]
+    fn reader_large_reads() {
+        let file_data: Vec<u8> = (0..100_000_u32).map(|i| (i % 251) as u8).collect();
+        let len = u32::try_from(file_data.len()).unwrap();
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("first", len, &*file_data)
+            .expect("failed to write file");
+        writer
+            .write_file("second", len, &*file_data)
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        // Mix buffered and direct reads within the same file.
+        for buffer_len in [1, 1000, 10 * 1024, 64 * 1024, 200_000] {
+            let mut reader = Reader::new(std::io::Cursor::new(archive.clone()));
+            let mut buffer = vec![0; buffer_len];
+            while let Some(mut file) = reader.read_file().expect("failed to read file") {
+                let mut small = [0; 7];
+                let n = file.read(&mut small).expect("failed to read file data");
+                let mut actual = small[..n].to_vec();
+                loop {
+                    let n = file.read(&mut buffer).expect("failed to read file data");
+                    if n == 0 {
+                        break;
+                    }
+                    actual.extend_from_slice(&buffer[..n]);
+                }
+                assert!(actual == file_data, "{buffer_len}");
+            }
+        }
+
+        // The output limit still applies to direct reads.
+        let mut reader = Reader::new(std::io::Cursor::new(archive));
+        reader.set_max_total_output(Some(50_000));
+        let mut file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        let mut buffer = vec![0; 64 * 1024];
+        let error = file
+            .read(&mut buffer)
+            .expect_err("output limit should be exceeded");
+        assert!(error.kind() == std::io::ErrorKind::Other);
+
+        // Nothing was taken from the archive, so smaller reads within the limit still line up.
+        let mut small = [0; 1000];
+        let n = file.read(&mut small).expect("failed to read file data");
+        assert!(small[..n] == file_data[..n]);
+    }
+
+    #[test]
+    fn reader_duplicate_groups() {
+        let mut writer = crate::Writer::new_vec();
+        for (name, data) in [
+            ("a", &b"blank"[..]),
+            ("b", b"unique"),
+            ("c", b"blank"),
+            ("d", b"other"),
+            ("e", b""),
+            ("f", b"other"),
+            ("g", b"blank"),
+        ] {
+            let len = u32::try_from(data.len()).unwrap();
+            writer
+                .write_file(name, len, data)
+                .expect("failed to write file");
+        }
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let mut reader = Reader::new(std::io::Cursor::new(archive));
+        let groups = reader
+            .duplicate_groups()
+            .expect("failed to find duplicates");
+        assert!(groups.len() == 2, "{groups:?}");
+        assert!(groups[0].names == ["a", "c", "g"]);
+        assert!(groups[0].size == 5);
+        assert!(groups[0].wasted_bytes() == 10);
+        assert!(groups[1].names == ["d", "f"]);
+        assert!(groups[1].wasted_bytes() == 5);
+    }
+
+    #[test]
+    fn reader_read_header_eof() {
+        for archive in [&b""[..], b"RGSS"] {
+            let mut reader = Reader::new(std::io::Cursor::new(archive));
+            let error = reader
+                .read_header()
+                .expect_err("header should be incomplete");
+            assert!(
+                matches!(&error, Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+            );
+        }
+    }
+
+    #[test]
+    fn reader_always_zero() {
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("test", 64 * 1024, &*vec![1; 64 * 1024])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+        let file_header_end = crate::HEADER_LEN + 8 + "test".len();
+
+        // A reader that returns nothing at all has no header.
+        let mut reader = Reader::new(ZeroAfter::new(archive.clone(), 0));
+        let error = reader.read_header().expect_err("header should be missing");
+        assert!(
+            matches!(&error, Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+
+        // Zero-byte reads partway through a header are an error, not the end of the archive.
+        for limit in [4, crate::HEADER_LEN + 2] {
+            let mut reader = Reader::new(ZeroAfter::new(archive.clone(), limit));
+            let error = reader
+                .read_file()
+                .expect_err("file header should be incomplete");
+            assert!(
+                matches!(&error, Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof),
+                "{limit}: {error:?}"
+            );
+        }
+
+        // Zero-byte reads in file data are an error for buffered and direct reads.
+        for buffer_len in [16, 64 * 1024] {
+            let mut reader = Reader::new(ZeroAfter::new(archive.clone(), file_header_end));
+            let mut file = reader
+                .read_file()
+                .expect("failed to read file")
+                .expect("missing file");
+            let mut buffer = vec![0; buffer_len];
+            let error = file
+                .read(&mut buffer)
+                .expect_err("file data should be missing");
+            assert!(error.kind() == std::io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn reader_progress_channel() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (tx, rx) = std::sync::mpsc::sync_channel(1024);
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.set_progress_channel(Some(tx));
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        drop(reader);
+
+        let events: Vec<ProgressEvent> = rx.iter().collect();
+        for (name, data) in files.iter() {
+            let started = events
+                .iter()
+                .filter(|event| matches!(event, ProgressEvent::Started { name: event_name, .. } if event_name == name))
+                .count();
+            let finished = events
+                .iter()
+                .filter(|event| matches!(event, ProgressEvent::Finished { name: event_name } if event_name == name))
+                .count();
+            assert!(started == 1, "{name}");
+            assert!(finished == 1, "{name}");
+
+            let last_progress = events.iter().rev().find_map(|event| match event {
+                ProgressEvent::Progress {
+                    name: event_name,
+                    bytes,
+                } if event_name == name => Some(*bytes),
+                _ => None,
+            });
+            let expected = (!data.is_empty()).then(|| u32::try_from(data.len()).unwrap());
+            assert!(last_progress == expected, "{name}");
+        }
+
+        // A full channel drops events instead of blocking.
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (tx, rx) = std::sync::mpsc::sync_channel(1);
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.set_progress_channel(Some(tx));
+        let (_files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        drop(reader);
+        assert!(rx.iter().count() == 1);
+
+        // Files found by name send the same events as files read in order.
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (tx, rx) = std::sync::mpsc::sync_channel(1024);
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.read_header().expect("failed to read header");
+        reader.set_progress_channel(Some(tx));
+        let (name, data) = &files[1];
+        let mut file = reader
+            .read_file_by_name(name)
+            .expect("failed to read file")
+            .expect("missing file");
+        std::io::copy(&mut file, &mut std::io::sink()).expect("failed to read file");
+        drop(reader);
+        let events: Vec<ProgressEvent> = rx.iter().collect();
+        assert!(
+            matches!(events.first(), Some(ProgressEvent::Started { name: event_name, size }) if event_name == name && usize::try_from(*size).unwrap() == data.len())
+        );
+        assert!(
+            matches!(events.last(), Some(ProgressEvent::Finished { name: event_name }) if event_name == name)
+        );
+    }
+
+    #[test]
+    fn reader_key() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let keys = Reader::new(std::io::Cursor::new(file.clone()))
+            .entry_keys()
+            .expect("failed to get keys");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.read_header().expect("failed to read header");
+        assert!(reader.key() == crate::DEFAULT_KEY);
+
+        let mut i = 0;
+        while let Some(mut file) = reader.read_file().expect("failed to read file") {
+            std::io::copy(&mut file, &mut std::io::sink()).expect("failed to read file");
+            // Reading file data does not change the key.
+            assert!(reader.key() == keys[i]);
+            i += 1;
+        }
+        assert!(i == keys.len());
+    }
+
+    #[test]
+    fn reader_archive_len() {
+        // Write a file header that claims far more data than is present.
+        let mut writer = crate::sans_io::Writer::new();
+        while !writer
+            .step_write_file_header("test", u32::MAX)
+            .expect("failed to write file header")
+            .is_done()
+        {}
+        let archive = writer.data().to_vec();
+        let archive_len = u64::try_from(archive.len()).unwrap();
+
+        let mut reader = Reader::new(std::io::Cursor::new(archive.clone()));
+        reader.set_archive_len(Some(archive_len));
+        let error = reader.read_file().expect_err("file size should be invalid");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::InvalidFileSize { size: u32::MAX })
+        ));
+
+        // Without a known length, the error is only found when reading the data.
+        let mut reader = Reader::new(std::io::Cursor::new(archive));
+        let mut file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        let error = file
+            .read_to_end(&mut Vec::new())
+            .expect_err("file data should be missing");
+        assert!(error.kind() == std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn reader_max_entries() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let num_entries = reader
+            .manifest()
+            .expect("failed to build manifest")
+            .entries
+            .len();
+
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        reader.set_max_entries(Some(num_entries));
+        reader
+            .manifest()
+            .expect("reading up to the limit should succeed");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        reader.set_max_entries(Some(num_entries - 1));
+        let error = reader
+            .manifest()
+            .expect_err("limit should have been exceeded");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::TooManyEntries { max }) if max == num_entries - 1
+        ));
+    }
+
+    #[test]
+    fn reader_file_remaining() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+
+        while let Some(mut file) = reader.read_file().expect("failed to read file") {
+            assert!(file.remaining() == file.size());
+            assert!(file.is_empty() == (file.size() == 0));
+
+            let mut expected_remaining = file.size();
+            let mut buffer = [0; 7];
+            loop {
+                let n = file.read(&mut buffer).expect("failed to read file");
+                if n == 0 {
+                    break;
+                }
+                expected_remaining -= u32::try_from(n).unwrap();
+                assert!(file.remaining() == expected_remaining);
+            }
+            assert!(file.remaining() == 0);
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn reader_from_bytes_crate() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let mut expected = Vec::new();
+        while let Some(mut file) = reader.read_file().expect("failed to read file") {
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer).expect("failed to read file");
+            expected.push((file.name().to_string(), buffer));
+        }
+
+        let mut reader = Reader::from_bytes_crate(bytes::Bytes::from(file));
+        let mut actual = Vec::new();
+        while let Some(mut file) = reader.read_file().expect("failed to read file") {
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer).expect("failed to read file");
+            actual.push((file.name().to_string(), buffer));
+        }
+
+        assert!(actual == expected);
+    }
+
+    #[test]
+    fn reader_try_into_inner() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        // Complete
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let (_files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        reader.try_into_inner().expect("archive should be complete");
+
+        // Unread files
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        reader.read_file().expect("failed to read file");
+        let (_file, error) = reader
+            .try_into_inner()
+            .expect_err("archive should have unread files");
+        assert!(matches!(error, Error::NotFinished));
+
+        // Partially read file
+        let mut reader = Reader::new(std::io::Cursor::new(file.clone()));
+        let mut first_file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(first_file.size() > 1);
+        first_file
+            .read_exact(&mut [0; 1])
+            .expect("failed to read file");
+        let (_file, error) = reader
+            .try_into_inner()
+            .expect_err("file should be partially read");
+        assert!(matches!(error, Error::NotFinished));
+
+        // Trailing bytes
+        let mut file = file;
+        file.push(1);
+        let mut reader = Reader::new(std::io::Cursor::new(file));
+        while let Ok(Some(_file)) = reader.read_file() {}
+        let (_file, error) = reader
+            .try_into_inner()
+            .expect_err("archive should have trailing bytes");
+        assert!(
+            matches!(error, Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+    }
+
     #[test]
     fn reader_trailing_bytes() {
         let mut file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
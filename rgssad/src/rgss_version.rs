@@ -0,0 +1,91 @@
+/// A version of RGSS, the scripting system of an RPG Maker engine.
+///
+/// Each engine names its archives with a different extension,
+/// but RPG Maker XP and RPG Maker VX share the same archive format, version 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RgssVersion {
+    /// RGSS1, used by RPG Maker XP.
+    ///
+    /// Archives use the "rgssad" extension and archive format version 1.
+    Rgss1,
+
+    /// RGSS2, used by RPG Maker VX.
+    ///
+    /// Archives use the "rgss2a" extension and archive format version 1.
+    /// Note that, despite the extension, this is not archive format version 2.
+    Rgss2,
+
+    /// RGSS3, used by RPG Maker VX Ace.
+    ///
+    /// Archives use the "rgss3a" extension and archive format version 3.
+    Rgss3,
+}
+
+impl RgssVersion {
+    /// Guess the RGSS version from an archive's file extension, without the leading `.`.
+    ///
+    /// This is case-insensitive.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        if extension.eq_ignore_ascii_case("rgssad") {
+            Some(Self::Rgss1)
+        } else if extension.eq_ignore_ascii_case("rgss2a") {
+            Some(Self::Rgss2)
+        } else if extension.eq_ignore_ascii_case("rgss3a") {
+            Some(Self::Rgss3)
+        } else {
+            None
+        }
+    }
+
+    /// Get the archive file extension for this version, without the leading `.`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Rgss1 => "rgssad",
+            Self::Rgss2 => "rgss2a",
+            Self::Rgss3 => "rgss3a",
+        }
+    }
+
+    /// Get the archive format version stored in the header of this version's archives.
+    pub fn archive_version(self) -> u8 {
+        match self {
+            Self::Rgss1 | Self::Rgss2 => 1,
+            Self::Rgss3 => 3,
+        }
+    }
+
+    /// Returns true if this crate's [`Reader`](crate::Reader) and [`Writer`](crate::Writer) can handle this version's archives.
+    pub fn is_supported(self) -> bool {
+        self.archive_version() == crate::VERSION
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_extension() {
+        assert!(RgssVersion::from_extension("rgssad") == Some(RgssVersion::Rgss1));
+        assert!(RgssVersion::from_extension("rgss2a") == Some(RgssVersion::Rgss2));
+        assert!(RgssVersion::from_extension("RGSS2A") == Some(RgssVersion::Rgss2));
+        assert!(RgssVersion::from_extension("rgss3a") == Some(RgssVersion::Rgss3));
+        assert!(RgssVersion::from_extension("zip").is_none());
+        assert!(RgssVersion::from_extension(".rgssad").is_none());
+
+        for version in [RgssVersion::Rgss1, RgssVersion::Rgss2, RgssVersion::Rgss3] {
+            assert!(RgssVersion::from_extension(version.extension()) == Some(version));
+        }
+    }
+
+    #[test]
+    fn archive_version() {
+        assert!(RgssVersion::Rgss1.archive_version() == 1);
+        assert!(RgssVersion::Rgss2.archive_version() == 1);
+        assert!(RgssVersion::Rgss3.archive_version() == 3);
+
+        assert!(RgssVersion::Rgss1.is_supported());
+        assert!(RgssVersion::Rgss2.is_supported());
+        assert!(!RgssVersion::Rgss3.is_supported());
+    }
+}
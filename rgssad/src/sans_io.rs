@@ -4,9 +4,13 @@ mod writer;
 pub use self::reader::Reader;
 pub use self::writer::Writer;
 use crate::MAX_FILE_NAME_LEN;
+use crate::VERSION;
 
 /// An error that may occur while using sans-io state machines.
+///
+/// New variants may be added in the future.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Invalid magic number
     InvalidMagic { magic: [u8; 7] },
@@ -29,6 +33,21 @@ pub enum Error {
     /// The provided file size does not match the file data's size.
     FileDataSizeMismatch { actual: u32, expected: u32 },
 
+    /// The file data size was larger than the configured maximum.
+    FileDataTooLarge { size: u32, max: u32 },
+
+    /// The total amount of decrypted file data exceeded the configured limit.
+    OutputLimitExceeded { limit: u64 },
+
+    /// The archive contained more entries than the configured limit.
+    TooManyEntries { max: usize },
+
+    /// A file's data would extend past the end of the archive.
+    InvalidFileSize { size: u32 },
+
+    /// A value was too large to be converted to the needed integer type.
+    ValueTooLarge,
+
     /// Invalid internal state, user error
     InvalidState,
 }
@@ -37,7 +56,14 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Self::InvalidMagic { magic } => write!(f, "magic number \"{magic:?}\" is invalid"),
-            Self::InvalidVersion { version } => write!(f, "version \"{version}\" is invalid"),
+            Self::InvalidVersion { version: 3 } => write!(
+                f,
+                "version \"3\" (\"rgss3a\") is not supported, only version \"{VERSION}\" is supported"
+            ),
+            Self::InvalidVersion { version } => write!(
+                f,
+                "version \"{version}\" is invalid, only version \"{VERSION}\" is supported (\"rgssad\" and \"rgss2a\" archives are both version \"{VERSION}\")"
+            ),
             Self::FileNameTooLongU32 { len } => write!(
                 f,
                 "file name {len} is too long, max length is {MAX_FILE_NAME_LEN}"
@@ -51,6 +77,22 @@ impl std::fmt::Display for Error {
                 f,
                 "file data size mismatch, expected {expected} but got {actual}"
             ),
+            Self::FileDataTooLarge { size, max } => write!(
+                f,
+                "file data size {size} is larger than the maximum of {max}"
+            ),
+            Self::OutputLimitExceeded { limit } => write!(
+                f,
+                "total decrypted file data exceeded the limit of {limit} bytes"
+            ),
+            Self::TooManyEntries { max } => {
+                write!(f, "archive contains more than the limit of {max} entries")
+            }
+            Self::InvalidFileSize { size } => write!(
+                f,
+                "file data size {size} extends past the end of the archive"
+            ),
+            Self::ValueTooLarge => write!(f, "value is too large for the target integer type"),
             Self::InvalidState => {
                 write!(f, "programmer error, invalid internal state for function")
             }
@@ -128,11 +170,39 @@ impl<T> WriterAction<T> {
 }
 
 /// A file header
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FileHeader {
     /// The file name
     pub name: String,
 
     /// The file data size.
     pub size: u32,
+
+    /// The offset of the file data from the start of the archive.
+    pub offset: u64,
+
+    /// The encryption key at the start of the file data.
+    pub key: u32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalid_version_display() {
+        let message = Error::InvalidVersion { version: 2 }.to_string();
+        assert!(message.contains("\"2\" is invalid"), "{message}");
+        assert!(
+            message.contains("only version \"1\" is supported"),
+            "{message}"
+        );
+        assert!(message.contains("\"rgss2a\""), "{message}");
+
+        let message = Error::InvalidVersion { version: 3 }.to_string();
+        assert!(
+            message.contains("\"rgss3a\") is not supported"),
+            "{message}"
+        );
+    }
 }
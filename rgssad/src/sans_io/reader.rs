@@ -1,14 +1,14 @@
 use super::Error;
 use super::FileHeader;
 use super::ReaderAction;
-use crate::crypt_file_data;
-use crate::crypt_name_bytes;
-use crate::crypt_u32;
+use crate::crypt::Crypt;
+use crate::crypt::StandardCrypt;
 use crate::DEFAULT_KEY;
 use crate::HEADER_LEN;
 use crate::MAGIC;
 use crate::MAGIC_LEN;
 use crate::MAX_FILE_NAME_LEN;
+use crate::MIN_BUFFER_CAPACITY;
 use crate::U32_LEN;
 use crate::VERSION;
 
@@ -16,30 +16,142 @@ const DEFAULT_BUFFER_CAPACITY: usize = 10 * 1024;
 
 /// A sans-io reader state machine.
 #[derive(Debug)]
-pub struct Reader {
+pub struct Reader<C = StandardCrypt> {
     buffer: oval::Buffer,
+    pub(crate) crypt: C,
 
     state: State,
     need_seek: bool,
     position: u64,
     next_file_position: u64,
     pub(crate) key: u32,
+    pub(crate) initial_key: u32,
+    total_output: u64,
+    pub(crate) max_total_output: Option<u64>,
+    num_entries: usize,
+    pub(crate) max_entries: Option<usize>,
+    pub(crate) start_position: u64,
+    pub(crate) archive_len: Option<u64>,
+    raw_headers: Option<Vec<u8>>,
 }
 
 impl Reader {
     /// Create a new reader state machine.
     pub fn new() -> Self {
+        Self::with_key(DEFAULT_KEY)
+    }
+
+    /// Create a new reader state machine with the given initial encryption key.
+    pub fn with_key(key: u32) -> Self {
+        Self::with_crypt(key, StandardCrypt)
+    }
+
+    /// Create a new reader state machine with the given internal buffer capacity.
+    ///
+    /// A larger buffer means fewer, larger reads.
+    /// Capacities smaller than [`MIN_BUFFER_CAPACITY`] are raised to it.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_crypt_and_capacity(DEFAULT_KEY, StandardCrypt, capacity)
+    }
+
+    /// Create a new reader state machine, seeded with an already-read header.
+    ///
+    /// The header is validated on the next step, as if it had been read normally.
+    pub fn with_header(header: [u8; HEADER_LEN]) -> Self {
+        let mut reader = Self::new();
+        reader.space()[..HEADER_LEN].copy_from_slice(&header);
+        reader.fill(HEADER_LEN);
+        reader
+    }
+}
+
+impl<C> Reader<C>
+where
+    C: Crypt,
+{
+    /// Create a new reader state machine with the given initial encryption key and encryption scheme.
+    pub fn with_crypt(key: u32, crypt: C) -> Self {
+        Self::with_crypt_and_capacity(key, crypt, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    fn with_crypt_and_capacity(key: u32, crypt: C, capacity: usize) -> Self {
         Self {
-            buffer: oval::Buffer::with_capacity(DEFAULT_BUFFER_CAPACITY),
+            buffer: oval::Buffer::with_capacity(std::cmp::max(capacity, MIN_BUFFER_CAPACITY)),
+            crypt,
 
             state: State::Header,
             need_seek: false,
             position: 0,
             next_file_position: 0,
-            key: DEFAULT_KEY,
+            key,
+            initial_key: key,
+            total_output: 0,
+            max_total_output: None,
+            num_entries: 0,
+            max_entries: None,
+            start_position: 0,
+            archive_len: None,
+            raw_headers: None,
         }
     }
 
+    /// Set the maximum number of file data bytes that may be decrypted, across all files.
+    ///
+    /// This protects against archives that declare huge sizes to exhaust memory or disk when extracted.
+    /// If set, reading file data past the limit returns [`Error::OutputLimitExceeded`].
+    /// By default, there is no limit.
+    pub fn set_max_total_output(&mut self, max_total_output: Option<u64>) {
+        self.max_total_output = max_total_output;
+    }
+
+    /// Set the maximum number of file headers that may be read.
+    ///
+    /// This protects against archives padded with huge numbers of entries.
+    /// If set, reading a file header past the limit returns [`Error::TooManyEntries`].
+    /// By default, there is no limit.
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.max_entries = max_entries;
+    }
+
+    /// Set the position of the start of the archive in the underlying stream.
+    ///
+    /// This is useful for archives embedded in a larger file.
+    /// Requested seeks are offset by this position,
+    /// while reported file offsets stay relative to the start of the archive.
+    /// The stream must already be positioned at the start of the archive before the header is read.
+    /// By default, this is 0.
+    pub fn set_start_position(&mut self, start_position: u64) {
+        self.start_position = start_position;
+    }
+
+    /// Set the length of the archive, if known.
+    ///
+    /// If set, reading a file header whose data would extend past this length returns [`Error::InvalidFileSize`].
+    /// This catches corrupt file sizes before a bogus seek is requested.
+    /// By default, the length is unknown.
+    pub fn set_archive_len(&mut self, archive_len: Option<u64>) {
+        self.archive_len = archive_len;
+    }
+
+    /// Set whether the raw bytes of the header and each file header should be captured.
+    ///
+    /// Captured bytes are stored as they appear in the archive, still encrypted,
+    /// and are appended in the order they are read.
+    /// Get them with [`Reader::raw_headers`].
+    /// Decoding is not affected.
+    /// Disabling capturing drops any captured bytes.
+    /// By default, nothing is captured.
+    pub fn set_capture_raw_headers(&mut self, capture_raw_headers: bool) {
+        self.raw_headers = capture_raw_headers.then(Vec::new);
+    }
+
+    /// Get the raw header bytes captured so far.
+    ///
+    /// This is empty if capturing was not enabled with [`Reader::set_capture_raw_headers`].
+    pub fn raw_headers(&self) -> &[u8] {
+        self.raw_headers.as_deref().unwrap_or(&[])
+    }
+
     /// Get a reference to the read buffer part where new data should be written.
     ///
     /// You should indicate how many bytes were written with `fill`.
@@ -57,6 +169,47 @@ impl Reader {
         self.buffer.available_data()
     }
 
+    /// Get the current encryption key.
+    ///
+    /// The key rotates as file headers are read.
+    /// After a file header is read, this is the key at the start of that file's data,
+    /// which is also the key for the next file header.
+    pub fn key(&self) -> u32 {
+        self.key
+    }
+
+    /// Get the name of the current internal state, like `"FileHeader"`.
+    ///
+    /// This is only meant for debugging drivers, and the names may change.
+    pub fn debug_state(&self) -> &'static str {
+        self.state.name()
+    }
+
+    /// Get the capacity of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Move buffered data to the start of the buffer if there is not enough space after it to read `size` bytes.
+    ///
+    /// The buffer only shifts itself once over half of it is consumed,
+    /// which is not enough for a file header in a buffer near [`MIN_BUFFER_CAPACITY`].
+    fn make_space(&mut self, size: usize) {
+        if self.buffer.available_space() < size {
+            self.buffer.shift();
+        }
+    }
+
+    /// Get the number of file data bytes left to read for the current file.
+    ///
+    /// This is 0 if no file header has been read.
+    pub fn remaining_file_data(&self) -> u32 {
+        match self.state {
+            State::FileData { remaining, .. } => remaining,
+            State::Header | State::FileHeader => 0,
+        }
+    }
+
     /// Tell the state machine that the seek it requested if finished.
     ///
     /// This will clear any buffered bytes.
@@ -73,6 +226,27 @@ impl Reader {
         self.buffer.reset();
     }
 
+    /// Jump to the data of the file described by `header`, as if its header had just been read.
+    ///
+    /// `header` should come from this archive.
+    /// This returns the position that the inner reader must be seeked to before stepping again.
+    /// Afterwards, file data is read from the start of this file,
+    /// and the next file header read is the one after this file.
+    pub fn jump_to_file(&mut self, header: &FileHeader) -> u64 {
+        self.buffer.reset();
+        self.position = header.offset;
+        self.next_file_position = header.offset + u64::from(header.size);
+        self.key = header.key;
+        self.need_seek = true;
+        self.state = State::FileData {
+            key: header.key,
+            counter: 0,
+            remaining: header.size,
+        };
+
+        self.start_position + header.offset
+    }
+
     /// Step the state machine, performing the action of reading and validating the header.
     ///
     /// If the header has already been read, `Ok(ReaderAction::Done(()))` is returned and no work is performed.
@@ -107,6 +281,9 @@ impl Reader {
 
         // We know the header len can fit in a u64.
         let header_len_u64 = u64::try_from(HEADER_LEN).unwrap();
+        if let Some(raw_headers) = self.raw_headers.as_mut() {
+            raw_headers.extend_from_slice(&data[..HEADER_LEN]);
+        }
         self.buffer.consume(HEADER_LEN);
         self.position = header_len_u64;
         self.next_file_position = header_len_u64;
@@ -132,7 +309,9 @@ impl Reader {
                 State::FileHeader => break,
                 State::FileData { .. } => {
                     if self.position != self.next_file_position {
-                        return Ok(ReaderAction::Seek(self.next_file_position));
+                        return Ok(ReaderAction::Seek(
+                            self.start_position + self.next_file_position,
+                        ));
                     }
 
                     self.state = State::FileHeader;
@@ -145,6 +324,7 @@ impl Reader {
         let data_len = data.len();
 
         if data_len < U32_LEN {
+            self.make_space(U32_LEN - data_len);
             return Ok(ReaderAction::Read(U32_LEN - data_len));
         }
 
@@ -152,7 +332,7 @@ impl Reader {
             // We check the buffer size above.
             let bytes = data[..U32_LEN].try_into().unwrap();
             let n = u32::from_le_bytes(bytes);
-            let n = crypt_u32(&mut key, n);
+            let n = self.crypt.crypt_u32(&mut key, n);
             if n > MAX_FILE_NAME_LEN {
                 return Err(Error::FileNameTooLongU32 { len: n });
             }
@@ -163,12 +343,13 @@ impl Reader {
 
         let file_header_size = (U32_LEN * 2) + file_name_len;
         if data_len < file_header_size {
+            self.make_space(file_header_size - data_len);
             return Ok(ReaderAction::Read(file_header_size - data_len));
         }
 
         let file_name = {
             let mut bytes = data[U32_LEN..U32_LEN + file_name_len].to_vec();
-            crypt_name_bytes(&mut key, &mut bytes);
+            self.crypt.crypt_name(&mut key, &mut bytes);
 
             // I'm fairly certain these are required to be ASCII, but I forget the source.
             //
@@ -182,16 +363,40 @@ impl Reader {
             let range = index..index + U32_LEN;
             let bytes = data[range].try_into().unwrap();
             let n = u32::from_le_bytes(bytes);
-            crypt_u32(&mut key, n)
+            self.crypt.crypt_u32(&mut key, n)
         };
 
+        if let Some(max) = self.max_entries {
+            if self.num_entries >= max {
+                return Err(Error::TooManyEntries { max });
+            }
+        }
+
         // This should not be able to overflow a u64.
         let file_header_size_u64 = u64::try_from(file_header_size).unwrap();
-        self.buffer.consume(file_header_size);
-        self.position += file_header_size_u64;
         // Calculate the offset of the next file:
         // size_of(file_name_len) + size_of(file_name) + size_of(file_data_len) + size_of(file_data)
-        self.next_file_position += file_header_size_u64 + u64::from(file_data_len);
+        let next_file_position = self
+            .next_file_position
+            .checked_add(file_header_size_u64 + u64::from(file_data_len))
+            .ok_or(Error::InvalidFileSize {
+                size: file_data_len,
+            })?;
+        if let Some(archive_len) = self.archive_len {
+            if next_file_position > archive_len {
+                return Err(Error::InvalidFileSize {
+                    size: file_data_len,
+                });
+            }
+        }
+
+        self.num_entries += 1;
+        if let Some(raw_headers) = self.raw_headers.as_mut() {
+            raw_headers.extend_from_slice(&data[..file_header_size]);
+        }
+        self.buffer.consume(file_header_size);
+        self.position += file_header_size_u64;
+        self.next_file_position = next_file_position;
         self.key = key;
         self.need_seek = true;
         self.state = State::FileData {
@@ -203,6 +408,8 @@ impl Reader {
         Ok(ReaderAction::Done(FileHeader {
             name: file_name,
             size: file_data_len,
+            offset: self.position,
+            key,
         }))
     }
 
@@ -241,15 +448,121 @@ impl Reader {
             return Ok(ReaderAction::Read(self.buffer.available_space()));
         }
 
-        let remaining_usize =
-            usize::try_from(*remaining).expect("remaining bytes cannot fit in a `usize`");
+        // If the remaining bytes cannot fit in a usize, they are larger than any buffer.
+        let remaining_usize = usize::try_from(*remaining).unwrap_or(usize::MAX);
         let len = std::cmp::min(data.len(), output_buffer.len());
         let len = std::cmp::min(len, remaining_usize);
-        let len_u32 = u32::try_from(len).expect("len cannot fit in a `u32`");
+        let len_u32 = u32::try_from(len).map_err(|_| Error::ValueTooLarge)?;
+
+        let total_output = self.total_output + u64::from(len_u32);
+        if let Some(limit) = self.max_total_output {
+            if total_output > limit {
+                return Err(Error::OutputLimitExceeded { limit });
+            }
+        }
+        self.total_output = total_output;
         let output_buffer = &mut output_buffer[..len];
 
         output_buffer.copy_from_slice(&data[..len]);
-        crypt_file_data(key, counter, output_buffer);
+        self.crypt.crypt_data(key, counter, output_buffer);
+        *remaining -= len_u32;
+        self.buffer.consume(len);
+        self.position += u64::from(len_u32);
+
+        Ok(ReaderAction::Done(len))
+    }
+
+    /// Check that decrypting `len` more file data bytes would not exceed the total output limit.
+    ///
+    /// This returns [`Error::OutputLimitExceeded`] if it would, like [`Reader::decrypt_file_data`].
+    pub(crate) fn check_output_limit(&self, len: usize) -> Result<(), Error> {
+        let Some(limit) = self.max_total_output else {
+            return Ok(());
+        };
+
+        let len = u64::try_from(len).map_err(|_| Error::ValueTooLarge)?;
+        if self.total_output.saturating_add(len) > limit {
+            return Err(Error::OutputLimitExceeded { limit });
+        }
+
+        Ok(())
+    }
+
+    /// Decrypt file data that was read directly from the archive into `data`, bypassing the buffer.
+    ///
+    /// This avoids copying file data through the buffer for large reads.
+    /// It may only be used while the buffer is empty,
+    /// and `data` must not be longer than the remaining file data.
+    /// Otherwise, [`Error::InvalidState`] is returned.
+    pub fn decrypt_file_data(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        if self.buffer.available_data() != 0 {
+            return Err(Error::InvalidState);
+        }
+
+        let State::FileData {
+            key,
+            counter,
+            remaining,
+        } = &mut self.state
+        else {
+            return Err(Error::InvalidState);
+        };
+
+        let len_u32 = u32::try_from(data.len()).map_err(|_| Error::ValueTooLarge)?;
+        if len_u32 > *remaining {
+            return Err(Error::InvalidState);
+        }
+
+        let total_output = self.total_output + u64::from(len_u32);
+        if let Some(limit) = self.max_total_output {
+            if total_output > limit {
+                return Err(Error::OutputLimitExceeded { limit });
+            }
+        }
+        self.total_output = total_output;
+
+        self.crypt.crypt_data(key, counter, data);
+        *remaining -= len_u32;
+        self.position += u64::from(len_u32);
+
+        Ok(())
+    }
+
+    /// Skip buffered file data without decrypting it.
+    ///
+    /// This returns the number of bytes skipped.
+    /// This will return `Ok(ReaderAction::Done(0))` if a file header has not been read,
+    /// or if there is no file data left.
+    /// Skipped data does not count towards the total output limit.
+    /// This will never request a seek.
+    pub fn step_skip_file_data(&mut self) -> Result<ReaderAction<usize>, Error> {
+        let remaining = loop {
+            match &mut self.state {
+                State::Header => {
+                    let action = self.step_read_header()?;
+                    if !action.is_done() {
+                        return Ok(action.map_done(|_| unreachable!()));
+                    }
+                }
+                State::FileHeader => return Ok(ReaderAction::Done(0)),
+                State::FileData { remaining, .. } => break remaining,
+            }
+        };
+
+        if *remaining == 0 {
+            return Ok(ReaderAction::Done(0));
+        }
+
+        let data_len = self.buffer.available_data();
+        if data_len == 0 {
+            return Ok(ReaderAction::Read(self.buffer.available_space()));
+        }
+
+        // If the remaining bytes cannot fit in a usize, they are larger than any buffer.
+        let remaining_usize = usize::try_from(*remaining).unwrap_or(usize::MAX);
+        let len = std::cmp::min(data_len, remaining_usize);
+        let len_u32 = u32::try_from(len).map_err(|_| Error::ValueTooLarge)?;
+
         *remaining -= len_u32;
         self.buffer.consume(len);
         self.position += u64::from(len_u32);
@@ -275,3 +588,72 @@ enum State {
         remaining: u32,
     },
 }
+
+impl State {
+    /// Get the name of this state.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Header => "Header",
+            Self::FileHeader => "FileHeader",
+            Self::FileData { .. } => "FileData",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reader_with_capacity() {
+        assert!(Reader::with_capacity(64 * 1024).capacity() == 64 * 1024);
+        assert!(Reader::with_capacity(0).capacity() == MIN_BUFFER_CAPACITY);
+    }
+
+    #[test]
+    fn reader_debug_state() {
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("test", 4, &b"test"[..])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+        let mut archive = &archive[..];
+
+        let mut reader = Reader::new();
+        let mut states = vec![reader.debug_state()];
+        let mut data = [0; 4];
+        loop {
+            let action = match reader.debug_state() {
+                "FileData" if reader.remaining_file_data() != 0 => reader
+                    .step_read_file_data(&mut data)
+                    .expect("failed to read file data")
+                    .map_done(|_| ()),
+                _ => reader
+                    .step_read_file_header()
+                    .expect("failed to read file header")
+                    .map_done(|_| ()),
+            };
+            match action {
+                ReaderAction::Read(size) => {
+                    let space = reader.space();
+                    let n = std::cmp::min(size, archive.len());
+                    if n == 0 {
+                        break;
+                    }
+                    space[..n].copy_from_slice(&archive[..n]);
+                    archive = &archive[n..];
+                    reader.fill(n);
+                }
+                ReaderAction::Seek(_) => reader.finish_seek(),
+                ReaderAction::Done(()) => {}
+            }
+
+            if states.last() != Some(&reader.debug_state()) {
+                states.push(reader.debug_state());
+            }
+        }
+
+        assert!(states == ["Header", "FileHeader", "FileData"], "{states:?}");
+        assert!(data == *b"test");
+    }
+}
@@ -1,13 +1,13 @@
 use super::Error;
 use super::WriterAction;
-use crate::crypt_file_data;
-use crate::crypt_name_bytes;
-use crate::crypt_u32;
+use crate::crypt::Crypt;
+use crate::crypt::StandardCrypt;
 use crate::DEFAULT_KEY;
 use crate::HEADER_LEN;
 use crate::MAGIC;
 use crate::MAGIC_LEN;
 use crate::MAX_FILE_NAME_LEN;
+use crate::MIN_BUFFER_CAPACITY;
 use crate::U32_LEN;
 use crate::VERSION;
 
@@ -15,26 +15,78 @@ const DEFAULT_BUFFER_CAPACITY: usize = 10 * 1024;
 
 /// A sans-io writer state machine.
 #[derive(Debug)]
-pub struct Writer {
+pub struct Writer<C = StandardCrypt> {
     buffer: oval::Buffer,
+    crypt: C,
     key: u32,
     state: State,
     remaining: u32,
     last_file_size: Option<u32>,
+    max_file_size: Option<u32>,
+    bytes_written: u64,
+    hasher: Option<Hasher>,
 }
 
 impl Writer {
     /// Create a new writer state machine.
     pub fn new() -> Self {
+        Self::with_key(DEFAULT_KEY)
+    }
+
+    /// Create a new writer state machine with the given initial encryption key.
+    pub fn with_key(key: u32) -> Self {
+        Self::with_crypt(key, StandardCrypt)
+    }
+
+    /// Create a new writer state machine with the given internal buffer capacity.
+    ///
+    /// A larger buffer means fewer, larger writes.
+    /// Capacities smaller than [`MIN_BUFFER_CAPACITY`] are raised to it.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_crypt_and_capacity(DEFAULT_KEY, StandardCrypt, capacity)
+    }
+}
+
+impl<C> Writer<C>
+where
+    C: Crypt,
+{
+    /// Create a new writer state machine with the given initial encryption key and encryption scheme.
+    pub fn with_crypt(key: u32, crypt: C) -> Self {
+        Self::with_crypt_and_capacity(key, crypt, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    fn with_crypt_and_capacity(key: u32, crypt: C, capacity: usize) -> Self {
         Self {
-            buffer: oval::Buffer::with_capacity(DEFAULT_BUFFER_CAPACITY),
-            key: DEFAULT_KEY,
+            buffer: oval::Buffer::with_capacity(std::cmp::max(capacity, MIN_BUFFER_CAPACITY)),
+            crypt,
+            key,
             state: State::Header,
             remaining: 0,
             last_file_size: None,
+            max_file_size: None,
+            bytes_written: 0,
+            hasher: None,
         }
     }
 
+    /// Set the maximum allowed file data size.
+    ///
+    /// The format allows any `u32` size,
+    /// but a limit can catch accidentally garbage sizes before they are written.
+    /// If set, writing a file header with a larger size returns [`Error::FileDataTooLarge`].
+    /// By default, there is no limit.
+    pub fn set_max_file_size(&mut self, max_file_size: Option<u32>) {
+        self.max_file_size = max_file_size;
+    }
+
+    /// Get the name of the current internal state, like `"FileHeader"`.
+    ///
+    /// This is only meant for debugging drivers, and the names may change.
+    pub fn debug_state(&self) -> &'static str {
+        self.state.name()
+    }
+
     /// Get a reference to the output data buffer where data should be taken from.
     ///
     /// The amount of data copied should be marked with [`consume`].
@@ -47,9 +99,53 @@ impl Writer {
         self.buffer.space()
     }
 
+    /// Set a hasher that every consumed output byte is fed through.
+    ///
+    /// This allows hashing an archive while it is written, instead of reading it back afterwards.
+    /// Get the hash with [`Writer::finalize_hash`].
+    pub fn set_hasher<H>(&mut self, hasher: H)
+    where
+        H: std::hash::Hasher + Send + 'static,
+    {
+        self.hasher = Some(Hasher(Box::new(hasher)));
+    }
+
+    /// Get the hash of all consumed output bytes so far.
+    ///
+    /// This is `None` if no hasher was set with [`Writer::set_hasher`].
+    pub fn finalize_hash(&self) -> Option<u64> {
+        self.hasher.as_ref().map(|hasher| hasher.0.finish())
+    }
+
     /// Consume a number of bytes from the output buffer.
     pub fn consume(&mut self, size: usize) {
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.0.write(&self.buffer.data()[..size]);
+        }
         self.buffer.consume(size);
+        // A usize always fits in a u64 on supported platforms.
+        self.bytes_written += u64::try_from(size).unwrap();
+    }
+
+    /// Get the total number of bytes consumed from the output buffer.
+    ///
+    /// Once all output has been consumed, this is the length of the archive.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Get the number of file data bytes passed to [`Writer::step_write_file_data`] for the last file header.
+    ///
+    /// This is 0 if no file header has been written yet.
+    pub fn file_data_written(&self) -> u32 {
+        self.last_file_size.map_or(0, |size| size - self.remaining)
+    }
+
+    /// Get the number of file data bytes left to write for the last file header.
+    ///
+    /// This is 0 if no file header has been written yet.
+    pub fn remaining_file_data(&self) -> u32 {
+        self.remaining
     }
 
     /// Step the state machine, performing the action of writing the header.
@@ -109,6 +205,12 @@ impl Writer {
             }
         }
 
+        if let Some(max) = self.max_file_size {
+            if size > max {
+                return Err(Error::FileDataTooLarge { size, max });
+            }
+        }
+
         let name_len = name.len();
         if name_len > usize::try_from(MAX_FILE_NAME_LEN).unwrap() {
             return Err(Error::FileNameTooLongUsize { len: name_len });
@@ -116,30 +218,40 @@ impl Writer {
         // We check the name size above.
         let name_len_u32 = u32::try_from(name_len).unwrap();
 
-        let space = self.buffer.space();
         let file_header_size = (U32_LEN * 2) + name_len;
-        if space.len() < file_header_size {
-            return Ok(WriterAction::Write);
+        if self.buffer.available_space() < file_header_size {
+            // The buffer only shifts itself once over half of it is consumed,
+            // which is not enough for a file header in a buffer near MIN_BUFFER_CAPACITY.
+            self.buffer.shift();
+            if self.buffer.available_space() < file_header_size {
+                return Ok(WriterAction::Write);
+            }
         }
+        let space = self.buffer.space();
 
         let mut key = self.key;
 
-        let data = crypt_u32(&mut key, name_len_u32);
+        let data = self.crypt.crypt_u32(&mut key, name_len_u32);
         let (bytes, space) = space.split_at_mut(U32_LEN);
         bytes.copy_from_slice(&data.to_le_bytes());
 
         let (bytes, space) = space.split_at_mut(name_len);
         bytes.copy_from_slice(name.as_bytes());
-        crypt_name_bytes(&mut key, bytes);
+        self.crypt.crypt_name(&mut key, bytes);
 
-        let data = crypt_u32(&mut key, size);
+        let data = self.crypt.crypt_u32(&mut key, size);
         let (bytes, _space) = space.split_at_mut(U32_LEN);
         bytes.copy_from_slice(&data.to_le_bytes());
 
         self.key = key;
         self.buffer.fill(file_header_size);
 
-        self.state = State::FileData { key, counter: 0 };
+        // A file with no data is finished as soon as its header is written.
+        self.state = if size == 0 {
+            State::FileHeader
+        } else {
+            State::FileData { key, counter: 0 }
+        };
         self.remaining = size;
         self.last_file_size = Some(size);
 
@@ -176,10 +288,18 @@ impl Writer {
             }
         };
 
-        let size_u32 = u32::try_from(size).expect("number of bytes written cannot fit in a `u32`");
+        let size_u32 = u32::try_from(size).map_err(|_| Error::ValueTooLarge)?;
+        if size_u32 > self.remaining {
+            // We never transition to the FileData state without setting the last_file_size field.
+            let expected = self.last_file_size.unwrap();
+            return Err(Error::FileDataSizeMismatch {
+                actual: (expected - self.remaining).saturating_add(size_u32),
+                expected,
+            });
+        }
 
         let space = self.buffer.space();
-        crypt_file_data(key, counter, &mut space[..size]);
+        self.crypt.crypt_data(key, counter, &mut space[..size]);
 
         self.remaining -= size_u32;
         if self.remaining == 0 {
@@ -198,9 +318,70 @@ impl Default for Writer {
     }
 }
 
+/// A boxed hasher, so that the writer can still derive [`Debug`].
+struct Hasher(Box<dyn std::hash::Hasher + Send>);
+
+impl std::fmt::Debug for Hasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Hasher").finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug)]
 enum State {
     Header,
     FileHeader,
     FileData { key: u32, counter: u8 },
 }
+
+impl State {
+    /// Get the name of this state.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Header => "Header",
+            Self::FileHeader => "FileHeader",
+            Self::FileData { .. } => "FileData",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn writer_debug_state() {
+        let mut writer = Writer::new();
+        assert!(writer.debug_state() == "Header");
+
+        while !writer
+            .step_write_file_header("test", 1)
+            .expect("failed to write file header")
+            .is_done()
+        {}
+        assert!(writer.debug_state() == "FileData");
+
+        writer.space()[0] = 0;
+        while !writer
+            .step_write_file_data(1)
+            .expect("failed to write file data")
+            .is_done()
+        {}
+        assert!(writer.debug_state() == "FileHeader");
+    }
+
+    #[test]
+    fn writer_file_data_size_too_large() {
+        let mut writer = Writer::new();
+        while !writer
+            .step_write_file_header("test", 1)
+            .expect("failed to write file header")
+            .is_done()
+        {}
+
+        let error = writer
+            .step_write_file_data(usize::MAX)
+            .expect_err("size should not fit in a u32");
+        assert!(matches!(error, Error::ValueTooLarge));
+    }
+}
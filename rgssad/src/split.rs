@@ -0,0 +1,170 @@
+use crate::Error;
+use crate::Writer;
+use std::io::Read;
+use std::io::Write;
+
+/// An archive writer that splits its output across multiple volumes.
+///
+/// The format has no native support for splitting,
+/// so volumes are plain byte ranges of a single archive.
+/// To read the archive, concatenate the volumes in order, then read the result as usual.
+///
+/// A new volume is only started between files,
+/// once the current volume has reached the maximum volume size.
+/// This means a volume may exceed the maximum size by up to one file.
+pub struct SplitWriter<F, W> {
+    writer: Writer<Volumes<F, W>>,
+}
+
+impl<F, W> SplitWriter<F, W>
+where
+    F: FnMut(usize) -> W,
+{
+    /// Create a new [`SplitWriter`].
+    ///
+    /// `new_volume` is called with the index of each volume to create its writer.
+    pub fn new(max_volume_size: u64, new_volume: F) -> Self {
+        Self {
+            writer: Writer::new(Volumes {
+                new_volume,
+                volumes: Vec::new(),
+                max_volume_size,
+                current_volume_size: 0,
+            }),
+        }
+    }
+
+    /// Get the volume writers, in order.
+    pub fn into_volumes(self) -> Vec<W> {
+        self.writer.into_inner().volumes
+    }
+}
+
+impl<F, W> SplitWriter<F, W>
+where
+    F: FnMut(usize) -> W,
+    W: Write,
+{
+    /// Write the archive header.
+    ///
+    /// See [`Writer::write_header`].
+    pub fn write_header(&mut self) -> Result<(), Error> {
+        self.writer.write_header()
+    }
+
+    /// Write a file, starting a new volume first if the current one is full.
+    ///
+    /// See [`Writer::write_file`].
+    pub fn write_file<R>(
+        &mut self,
+        file_name: &str,
+        file_size: u32,
+        file_data: R,
+    ) -> Result<(), Error>
+    where
+        R: Read,
+    {
+        if self.writer.bytes_written_for_current_file() == 0 {
+            self.writer.get_mut().roll_if_full()?;
+        }
+
+        self.writer.write_file(file_name, file_size, file_data)
+    }
+
+    /// Finish writing.
+    ///
+    /// See [`Writer::finish`].
+    pub fn finish(&mut self) -> Result<(), Error> {
+        self.writer.finish()
+    }
+}
+
+impl<F, W> std::fmt::Debug for SplitWriter<F, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SplitWriter").finish_non_exhaustive()
+    }
+}
+
+/// The volumes of a [`SplitWriter`].
+struct Volumes<F, W> {
+    new_volume: F,
+    volumes: Vec<W>,
+    max_volume_size: u64,
+    current_volume_size: u64,
+}
+
+impl<F, W> Volumes<F, W>
+where
+    F: FnMut(usize) -> W,
+    W: Write,
+{
+    /// Get the current volume, creating the first one if needed.
+    fn current(&mut self) -> &mut W {
+        if self.volumes.is_empty() {
+            self.volumes.push((self.new_volume)(0));
+        }
+
+        // We create a volume above if there are none.
+        self.volumes.last_mut().unwrap()
+    }
+
+    /// Start a new volume if the current one is full.
+    fn roll_if_full(&mut self) -> std::io::Result<()> {
+        if self.volumes.is_empty() || self.current_volume_size < self.max_volume_size {
+            return Ok(());
+        }
+
+        self.current().flush()?;
+        let index = self.volumes.len();
+        self.volumes.push((self.new_volume)(index));
+        self.current_volume_size = 0;
+
+        Ok(())
+    }
+}
+
+impl<F, W> Write for Volumes<F, W>
+where
+    F: FnMut(usize) -> W,
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.current().write(buf)?;
+        // A usize always fits in a u64 on supported platforms.
+        self.current_volume_size += u64::try_from(n).unwrap();
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.current().flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::VX_TEST_GAME;
+
+    #[test]
+    fn split_writer_round_trip() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let mut reader = crate::Reader::new(std::io::Cursor::new(file.clone()));
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+
+        let mut writer = SplitWriter::new(1024, |_index| Vec::new());
+        writer.write_header().expect("failed to write header");
+        for (file_name, file_data) in files.iter() {
+            let len = u32::try_from(file_data.len()).expect("file data too large");
+            writer
+                .write_file(file_name, len, &**file_data)
+                .expect("failed to write file");
+        }
+        writer.finish().expect("failed to flush");
+        let volumes = writer.into_volumes();
+
+        assert!(volumes.len() > 1);
+        assert!(volumes.iter().all(|volume| !volume.is_empty()));
+        assert!(volumes.concat() == file);
+    }
+}
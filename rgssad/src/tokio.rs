@@ -7,6 +7,7 @@ pub use self::writer::TokioWriter;
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test::ZeroAfter;
     use crate::test::VX_TEST_GAME;
     use std::io::Seek;
     use std::io::SeekFrom;
@@ -101,4 +102,244 @@ mod test {
         // Ensure archives are byte-for-byte equivalent.
         assert!(&new_file == file.get_ref());
     }
+
+    #[tokio::test]
+    async fn reader_truncated_file_data() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+
+        // Find the end of the first file header, then cut the file data short.
+        let mut reader = crate::Reader::new(std::io::Cursor::new(file.clone()));
+        let manifest = reader.manifest().expect("failed to build manifest");
+        let first_entry = &manifest.entries[0];
+        assert!(first_entry.size > 1);
+        let header_end = crate::HEADER_LEN + 8 + first_entry.name.len();
+        let data_len = usize::try_from(first_entry.size).unwrap();
+        let file = file[..header_end + data_len / 2].to_vec();
+
+        let mut reader = TokioReader::new(std::io::Cursor::new(file));
+        let mut first_file = reader
+            .read_file()
+            .await
+            .expect("failed to read file")
+            .expect("missing file");
+        let mut buffer = Vec::new();
+        let error = first_file
+            .read_to_end(&mut buffer)
+            .await
+            .expect_err("file data should be truncated");
+        assert!(error.kind() == std::io::ErrorKind::UnexpectedEof);
+        assert!(buffer.len() == data_len / 2);
+    }
+
+    #[tokio::test]
+    async fn reader_read_header_eof() {
+        for archive in [&b""[..], b"RGSS"] {
+            let mut reader = TokioReader::new(std::io::Cursor::new(archive));
+            let error = reader
+                .read_header()
+                .await
+                .expect_err("header should be incomplete");
+            assert!(
+                matches!(&error, crate::Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn reader_always_zero() {
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("test", 64 * 1024, &*vec![1; 64 * 1024])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+        let file_header_end = crate::HEADER_LEN + 8 + "test".len();
+
+        // A reader that returns nothing at all has no header.
+        let mut reader = TokioReader::new(ZeroAfter::new(archive.clone(), 0));
+        let error = reader
+            .read_header()
+            .await
+            .expect_err("header should be missing");
+        assert!(
+            matches!(&error, crate::Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+
+        // Zero-byte reads partway through a header are an error, not the end of the archive.
+        for limit in [4, crate::HEADER_LEN + 2] {
+            let mut reader = TokioReader::new(ZeroAfter::new(archive.clone(), limit));
+            let error = reader
+                .read_file()
+                .await
+                .expect_err("file header should be incomplete");
+            assert!(
+                matches!(&error, crate::Error::Io(error) if error.kind() == std::io::ErrorKind::UnexpectedEof),
+                "{limit}: {error:?}"
+            );
+        }
+
+        // Zero-byte reads in file data are an error.
+        for buffer_len in [16, 64 * 1024] {
+            let mut reader = TokioReader::new(ZeroAfter::new(archive.clone(), file_header_end));
+            let mut file = reader
+                .read_file()
+                .await
+                .expect("failed to read file")
+                .expect("missing file");
+            let mut buffer = vec![0; buffer_len];
+            let error = file
+                .read(&mut buffer)
+                .await
+                .expect_err("file data should be missing");
+            assert!(error.kind() == std::io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[tokio::test]
+    async fn reader_large_file_multiple_polls() {
+        let file_data: Vec<u8> = (0..256 * 1024).map(|i| (i % 251) as u8).collect();
+        let file_size = u32::try_from(file_data.len()).expect("file data too large");
+        let mut writer = crate::Writer::new_vec();
+        writer
+            .write_file("large", file_size, &*file_data)
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let mut reader = TokioReader::new(std::io::Cursor::new(archive));
+        let mut file = reader
+            .read_file()
+            .await
+            .expect("failed to read file")
+            .expect("missing file");
+
+        // A single read never returns the whole file, but repeated reads do.
+        let mut buffer = vec![0; file_data.len()];
+        let mut len = 0;
+        let mut num_reads = 0;
+        while len < buffer.len() {
+            let n = file
+                .read(&mut buffer[len..])
+                .await
+                .expect("failed to read file");
+            assert!(n > 0);
+            assert!(n < file_data.len());
+            len += n;
+            num_reads += 1;
+        }
+        assert!(num_reads > 1);
+        assert!(buffer == file_data);
+    }
+
+    #[tokio::test]
+    async fn reader_pending_resumes() {
+        use std::pin::Pin;
+        use std::task::Context;
+        use std::task::Poll;
+        use tokio::io::AsyncRead;
+        use tokio::io::AsyncSeek;
+        use tokio::io::ReadBuf;
+
+        /// A reader that returns `Pending` before every read, and reads at most 100 bytes at a time.
+        struct PendingReader<R> {
+            inner: R,
+            pending: bool,
+        }
+
+        impl<R> AsyncRead for PendingReader<R>
+        where
+            R: AsyncRead + Unpin,
+        {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                self.pending = !self.pending;
+                if self.pending {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+
+                let len = std::cmp::min(buf.remaining(), 100);
+                let mut limited = ReadBuf::new(buf.initialize_unfilled_to(len));
+                let result = Pin::new(&mut self.inner).poll_read(cx, &mut limited);
+                let n = limited.filled().len();
+                buf.advance(n);
+                result
+            }
+        }
+
+        impl<R> AsyncSeek for PendingReader<R>
+        where
+            R: AsyncSeek + Unpin,
+        {
+            fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+                Pin::new(&mut self.inner).start_seek(position)
+            }
+
+            fn poll_complete(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+            ) -> Poll<std::io::Result<u64>> {
+                Pin::new(&mut self.inner).poll_complete(cx)
+            }
+        }
+
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let (files, error) =
+            crate::Reader::new(std::io::Cursor::new(file.clone())).read_until_error();
+        assert!(error.is_none());
+
+        let mut reader = TokioReader::new(PendingReader {
+            inner: std::io::Cursor::new(file),
+            pending: false,
+        });
+        let mut index = 0;
+        while let Some(mut file) = reader.read_file().await.expect("failed to read file") {
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .await
+                .expect("failed to read file");
+            assert!(file.name() == files[index].0);
+            assert!(buffer == files[index].1);
+            index += 1;
+        }
+        assert!(index == files.len());
+    }
+
+    #[tokio::test]
+    async fn writer_stream() {
+        let file = std::fs::read(VX_TEST_GAME).expect("failed to open archive");
+        let file = std::io::Cursor::new(file);
+        let mut reader = TokioReader::new(file);
+        reader.read_header().await.expect("failed to read header");
+
+        // Read entire archive into Vec.
+        let mut files = Vec::new();
+        while let Some(mut file) = reader.read_file().await.expect("failed to read file") {
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)
+                .await
+                .expect("failed to read file");
+            files.push((file.name().to_string(), buffer));
+        }
+
+        // Stream all files into new archive.
+        let entries = futures_util::stream::iter(files.iter().map(|(file_name, file_data)| {
+            let len = u32::try_from(file_data.len()).expect("file data too large");
+            (file_name.clone(), len, &**file_data)
+        }));
+        let mut new_file = Vec::<u8>::new();
+        let mut writer = TokioWriter::new(&mut new_file);
+        writer.write_header().await.expect("failed to write header");
+        writer
+            .write_stream(entries)
+            .await
+            .expect("failed to write stream");
+        writer.finish().await.expect("failed to flush");
+
+        let file = reader.into_inner();
+
+        // Ensure archives are byte-for-byte equivalent.
+        assert!(&new_file == file.get_ref());
+    }
 }
@@ -35,6 +35,49 @@ impl<R> TokioReader<R> {
     pub fn get_mut(&mut self) -> &mut R {
         &mut self.reader
     }
+
+    /// Set the maximum number of file data bytes that may be decrypted, across all files.
+    ///
+    /// See [`crate::sans_io::Reader::set_max_total_output`].
+    pub fn set_max_total_output(&mut self, max_total_output: Option<u64>) {
+        self.state_machine.set_max_total_output(max_total_output);
+    }
+
+    /// Set the maximum number of files that may be read.
+    ///
+    /// See [`crate::sans_io::Reader::set_max_entries`].
+    pub fn set_max_entries(&mut self, max_entries: Option<usize>) {
+        self.state_machine.set_max_entries(max_entries);
+    }
+
+    /// Get the current encryption key.
+    ///
+    /// See [`crate::sans_io::Reader::key`].
+    pub fn key(&self) -> u32 {
+        self.state_machine.key()
+    }
+
+    /// Set the length of the archive, if known.
+    ///
+    /// See [`crate::sans_io::Reader::set_archive_len`].
+    pub fn set_archive_len(&mut self, archive_len: Option<u64>) {
+        self.state_machine.set_archive_len(archive_len);
+    }
+
+    /// Set whether the raw bytes of the header and each file header should be captured.
+    ///
+    /// See [`crate::sans_io::Reader::set_capture_raw_headers`].
+    pub fn set_capture_raw_headers(&mut self, capture_raw_headers: bool) {
+        self.state_machine
+            .set_capture_raw_headers(capture_raw_headers);
+    }
+
+    /// Get the raw header bytes captured so far.
+    ///
+    /// See [`crate::sans_io::Reader::raw_headers`].
+    pub fn raw_headers(&self) -> &[u8] {
+        self.state_machine.raw_headers()
+    }
 }
 
 impl<R> TokioReader<R>
@@ -48,6 +91,13 @@ where
                 ReaderAction::Read(size) => {
                     let space = self.state_machine.space();
                     let n = self.reader.read(&mut space[..size]).await?;
+                    if n == 0 {
+                        // The state machine only requests a read if the header is incomplete.
+                        return Err(Error::Io(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "archive ended before the header",
+                        )));
+                    }
                     self.state_machine.fill(n);
                 }
                 ReaderAction::Done(()) => return Ok(()),
@@ -85,6 +135,7 @@ where
                     return Ok(Some(File {
                         name: file_header.name,
                         size,
+                        offset: file_header.offset,
                         reader: &mut self.reader,
                         state_machine: &mut self.state_machine,
                     }));
@@ -96,10 +147,17 @@ where
 
 pin_project_lite::pin_project! {
     /// An archive file
+    ///
+    /// Each call to `poll_read` decrypts at most one internal buffer's worth of data,
+    /// about 10 KiB, even if the output buffer is larger.
+    /// This keeps large files from blocking the runtime,
+    /// at the cost of partially filling large output buffers.
+    /// Callers should read in a loop, for example with [`AsyncReadExt::read_to_end`].
     #[derive(Debug)]
     pub struct File<'a, R> {
         name: String,
         size: u32,
+        offset: u64,
 
         #[pin]
         reader: &'a mut R,
@@ -117,6 +175,21 @@ impl<R> File<'_, R> {
     pub fn size(&self) -> u32 {
         self.size
     }
+
+    /// Get the offset of the file data from the start of the archive.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The number of bytes of file data left to read.
+    pub fn remaining(&self) -> u32 {
+        self.state_machine.remaining_file_data()
+    }
+
+    /// Returns true if this file has no data.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
 }
 
 impl<'a, R> AsyncRead for File<'a, R>
@@ -134,7 +207,7 @@ where
             let action = this
                 .state_machine
                 .step_read_file_data(buffer.initialize_unfilled())
-                .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
+                .map_err(std::io::Error::other)?;
 
             match action {
                 ReaderAction::Read(size) => {
@@ -147,6 +220,13 @@ where
                     ready!(this.reader.as_mut().poll_read(cx, &mut space))?;
 
                     let n = space.filled().len();
+                    if n == 0 {
+                        // The state machine only requests a read if file data remains.
+                        return Poll::Ready(Err(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "archive ended before the file data",
+                        )));
+                    }
                     this.state_machine.fill(n);
                 }
                 ReaderAction::Seek(_) => unreachable!(),
@@ -1,5 +1,7 @@
 use crate::sans_io::WriterAction;
 use crate::Error;
+use futures_util::Stream;
+use futures_util::StreamExt;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWrite;
@@ -44,6 +46,38 @@ impl<W> TokioWriter<W> {
     pub fn get_mut(&mut self) -> &mut W {
         &mut self.writer
     }
+
+    /// Set the maximum allowed file data size.
+    ///
+    /// See [`crate::sans_io::Writer::set_max_file_size`].
+    pub fn set_max_file_size(&mut self, max_file_size: Option<u32>) {
+        self.state_machine.set_max_file_size(max_file_size);
+    }
+
+    /// Get the total number of archive bytes passed to the inner writer.
+    ///
+    /// See [`crate::sans_io::Writer::bytes_written`].
+    pub fn bytes_written(&self) -> u64 {
+        self.state_machine.bytes_written()
+    }
+
+    /// Set a hasher that every written archive byte is fed through.
+    ///
+    /// See [`crate::sans_io::Writer::set_hasher`].
+    pub fn set_hasher<H>(&mut self, hasher: H)
+    where
+        H: std::hash::Hasher + Send + 'static,
+    {
+        self.state_machine.set_hasher(hasher);
+    }
+
+    /// Get the hash of all written archive bytes so far.
+    ///
+    /// Call this after finishing to get the hash of the whole archive.
+    /// See [`crate::sans_io::Writer::finalize_hash`].
+    pub fn finalize_hash(&self) -> Option<u64> {
+        self.state_machine.finalize_hash()
+    }
 }
 
 impl<W> TokioWriter<W>
@@ -163,6 +197,26 @@ where
         }
     }
 
+    /// Write files from a stream.
+    ///
+    /// Each item is a file name, size, and data, as passed to [`TokioWriter::write_file`].
+    /// Files are written as they arrive.
+    /// The next item is only polled after the previous file is fully written,
+    /// so at most one entry is held at a time.
+    /// Unlike [`TokioWriter::write_file`], this function cannot be retried.
+    pub async fn write_stream<S, R>(&mut self, entries: S) -> Result<(), Error>
+    where
+        S: Stream<Item = (String, u32, R)>,
+        R: AsyncRead + Unpin,
+    {
+        let mut entries = std::pin::pin!(entries);
+        while let Some((file_name, file_size, file_data)) = entries.next().await {
+            self.write_file(&file_name, file_size, file_data).await?;
+        }
+
+        Ok(())
+    }
+
     /// Finish writing.
     ///
     /// This is only a convenience function to call the inner [`Write`] object's [`Write::flush`] method.
@@ -1,7 +1,14 @@
+use crate::crypt::Crypt;
+use crate::crypt::StandardCrypt;
 use crate::sans_io::WriterAction;
 use crate::Error;
+use std::collections::HashSet;
+use std::io::Cursor;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 
 #[derive(Debug)]
 enum State {
@@ -12,7 +19,7 @@ enum State {
 
 /// The archive writer.
 #[derive(Debug)]
-pub struct Writer<W> {
+pub struct Writer<W, C = StandardCrypt> {
     /// The inner writer.
     writer: W,
 
@@ -20,16 +27,62 @@ pub struct Writer<W> {
     state: State,
 
     /// The state machine
-    state_machine: crate::sans_io::Writer,
+    state_machine: crate::sans_io::Writer<C>,
+
+    /// The buffer for reading ahead from file data sources.
+    ///
+    /// If empty, file data is read directly into the state machine's buffer.
+    read_ahead: Vec<u8>,
+
+    /// The range of bytes in the read ahead buffer that have not been passed to the state machine yet.
+    read_ahead_range: std::ops::Range<usize>,
+
+    /// The name of the last file whose header was written.
+    file_name: String,
+
+    /// The names of all written files, if duplicates are rejected.
+    file_names: Option<HashSet<String>>,
 }
 
 impl<W> Writer<W> {
     /// Create an archive writer around a writer.
     pub fn new(writer: W) -> Writer<W> {
+        Self::with_key(writer, crate::DEFAULT_KEY)
+    }
+
+    /// Create an archive writer around a writer, with the given initial encryption key.
+    pub fn with_key(writer: W, key: u32) -> Writer<W> {
+        Self::with_crypt(writer, key, StandardCrypt)
+    }
+
+    /// Create an archive writer around a writer, with the given internal buffer capacity.
+    ///
+    /// See [`crate::sans_io::Writer::with_capacity`].
+    pub fn with_capacity(writer: W, capacity: usize) -> Writer<W> {
+        Self::from_state_machine(writer, crate::sans_io::Writer::with_capacity(capacity))
+    }
+}
+
+impl<W, C> Writer<W, C>
+where
+    C: Crypt,
+{
+    /// Create an archive writer around a writer, with the given initial encryption key and encryption scheme.
+    ///
+    /// See [`Crypt`].
+    pub fn with_crypt(writer: W, key: u32, crypt: C) -> Writer<W, C> {
+        Self::from_state_machine(writer, crate::sans_io::Writer::with_crypt(key, crypt))
+    }
+
+    fn from_state_machine(writer: W, state_machine: crate::sans_io::Writer<C>) -> Writer<W, C> {
         Writer {
             writer,
             state: State::FileHeader,
-            state_machine: crate::sans_io::Writer::new(),
+            state_machine,
+            read_ahead: Vec::new(),
+            read_ahead_range: 0..0,
+            file_name: String::new(),
+            file_names: None,
         }
     }
 
@@ -38,15 +91,117 @@ impl<W> Writer<W> {
         self.writer
     }
 
+    /// Get the inner writer, checking that the archive is complete.
+    ///
+    /// This returns [`Error::NotFinished`] if the header was not written,
+    /// or if a file was only partially written.
+    /// On error, the inner writer is returned alongside the error.
+    pub fn try_into_inner(self) -> Result<W, (W, Error)> {
+        let header_len = u64::try_from(crate::HEADER_LEN).unwrap();
+        let finished = matches!(self.state, State::FileHeader)
+            && self.state_machine.remaining_file_data() == 0
+            && self.state_machine.bytes_written() >= header_len;
+        if !finished {
+            return Err((self.writer, Error::NotFinished));
+        }
+
+        Ok(self.writer)
+    }
+
+    /// Get a ref to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
     /// Get a mutable ref to the inner writer.
     pub fn get_mut(&mut self) -> &mut W {
         &mut self.writer
     }
+
+    /// Set the maximum allowed file data size.
+    ///
+    /// See [`crate::sans_io::Writer::set_max_file_size`].
+    pub fn set_max_file_size(&mut self, max_file_size: Option<u32>) {
+        self.state_machine.set_max_file_size(max_file_size);
+    }
+
+    /// Set whether writing a file with the same name as an earlier file should fail.
+    ///
+    /// If enabled, [`Writer::write_file`] returns [`Error::DuplicateFileName`] for repeated names,
+    /// before anything is written for the file.
+    /// This keeps every written name in memory, so it is disabled by default.
+    /// Only files written after enabling this are checked.
+    pub fn set_reject_duplicates(&mut self, reject_duplicates: bool) {
+        if !reject_duplicates {
+            self.file_names = None;
+        } else if self.file_names.is_none() {
+            self.file_names = Some(HashSet::new());
+        }
+    }
+
+    /// Set the number of bytes to read from file data sources at once.
+    ///
+    /// By default, file data is read directly into the internal buffer,
+    /// which may lead to many small reads for large files.
+    /// A larger read ahead buffer reduces the number of reads from sources, like files.
+    /// Setting this to 0 disables the read ahead buffer.
+    /// Sizes over `u32::MAX` are clamped to it, as no file can be larger.
+    ///
+    /// # Panics
+    /// This will panic if a file is partially written.
+    pub fn set_read_ahead(&mut self, size: usize) {
+        assert!(
+            self.read_ahead_range.is_empty(),
+            "cannot change the read ahead size while a file is partially written"
+        );
+        let max_size = usize::try_from(u32::MAX).unwrap_or(usize::MAX);
+        self.read_ahead = vec![0; std::cmp::min(size, max_size)];
+        self.read_ahead_range = 0..0;
+    }
+
+    /// Get the total number of archive bytes passed to the inner writer.
+    ///
+    /// See [`crate::sans_io::Writer::bytes_written`].
+    pub fn bytes_written(&self) -> u64 {
+        self.state_machine.bytes_written()
+    }
+
+    /// Set a hasher that every written archive byte is fed through.
+    ///
+    /// See [`crate::sans_io::Writer::set_hasher`].
+    pub fn set_hasher<H>(&mut self, hasher: H)
+    where
+        H: std::hash::Hasher + Send + 'static,
+    {
+        self.state_machine.set_hasher(hasher);
+    }
+
+    /// Get the hash of all written archive bytes so far.
+    ///
+    /// Call this after finishing to get the hash of the whole archive.
+    /// See [`crate::sans_io::Writer::finalize_hash`].
+    pub fn finalize_hash(&self) -> Option<u64> {
+        self.state_machine.finalize_hash()
+    }
 }
 
-impl<W> Writer<W>
+impl Writer<Cursor<Vec<u8>>> {
+    /// Create an archive writer that writes to an in-memory buffer.
+    pub fn new_vec() -> Self {
+        Self::new(Cursor::new(Vec::new()))
+    }
+
+    /// Finish writing and get the in-memory archive.
+    pub fn into_vec(mut self) -> Result<Vec<u8>, Error> {
+        self.finish()?;
+        Ok(self.writer.into_inner())
+    }
+}
+
+impl<W, C> Writer<W, C>
 where
     W: Write,
+    C: Crypt,
 {
     /// Write the archive header.
     ///
@@ -78,12 +233,40 @@ where
         }
     }
 
+    /// Get the number of bytes of the current file's data that were taken from its source.
+    ///
+    /// This is 0 if no file is partially written.
+    /// See [`Writer::write_file`] for how to use this to retry.
+    pub fn bytes_written_for_current_file(&self) -> u32 {
+        match self.state {
+            State::FileHeader => 0,
+            State::FileData { size } => {
+                // Bytes read into the buffers but not yet encrypted were still taken from the source.
+                // A source that returns more data than the file size can push this past a u32,
+                // but writing that data fails anyways, so saturate instead of panicking.
+                let size = u32::try_from(size).unwrap_or(u32::MAX);
+                let read_ahead = u32::try_from(self.read_ahead_range.len()).unwrap_or(u32::MAX);
+                self.state_machine
+                    .file_data_written()
+                    .saturating_add(size)
+                    .saturating_add(read_ahead)
+            }
+            State::Flush => self.state_machine.file_data_written(),
+        }
+    }
+
     /// Write a file.
     ///
     /// An file is composed of a name (path), size, and data.
     /// This function may be retried.
     /// To retry, call this function with the same arguments.
     /// Note that if anything other than an I/O error occurs, the written bytes are likely corrupted.
+    ///
+    /// When retrying, `file_data` must continue from where the failed call left off.
+    /// Passing the same reader again does this automatically.
+    /// If a fresh reader is used instead,
+    /// the first [`Writer::bytes_written_for_current_file`] bytes must be skipped first,
+    /// as they have already been taken by the writer.
     pub fn write_file<R>(
         &mut self,
         file_name: &str,
@@ -96,6 +279,14 @@ where
         loop {
             match &mut self.state {
                 State::FileHeader => {
+                    if let Some(file_names) = self.file_names.as_ref() {
+                        if file_names.contains(file_name) {
+                            return Err(Error::DuplicateFileName {
+                                name: file_name.to_string(),
+                            });
+                        }
+                    }
+
                     let action = self
                         .state_machine
                         .step_write_file_header(file_name, file_size)?;
@@ -107,6 +298,11 @@ where
                             self.state_machine.consume(size);
                         }
                         WriterAction::Done(()) => {
+                            if let Some(file_names) = self.file_names.as_mut() {
+                                file_names.insert(file_name.to_string());
+                            }
+                            self.file_name.clear();
+                            self.file_name.push_str(file_name);
                             self.state = State::FileData { size: 0 };
                         }
                     }
@@ -123,7 +319,20 @@ where
                                 break space;
                             }
                         };
-                        let n = file_data.read(space)?;
+                        let n = if self.read_ahead.is_empty() {
+                            file_data.read(space)?
+                        } else {
+                            if self.read_ahead_range.is_empty() {
+                                let n = file_data.read(&mut self.read_ahead)?;
+                                self.read_ahead_range = 0..n;
+                            }
+
+                            let pending = &self.read_ahead[self.read_ahead_range.clone()];
+                            let n = std::cmp::min(pending.len(), space.len());
+                            space[..n].copy_from_slice(&pending[..n]);
+                            self.read_ahead_range.start += n;
+                            n
+                        };
                         if n == 0 {
                             self.state = State::Flush;
                             continue;
@@ -161,10 +370,117 @@ where
         }
     }
 
+    /// Write a file, using the remaining length of `file_data` as its size.
+    ///
+    /// The size is found by seeking to the end of `file_data`, then back to where it was.
+    /// This avoids passing a size that does not match the data, like with [`Writer::write_file`].
+    /// Only the data from the current position of `file_data` is written.
+    /// Unlike [`Writer::write_file`], this cannot be retried after failing mid-file,
+    /// as `file_data` will no longer be at the start of the file data.
+    pub fn write_file_verified<R>(&mut self, file_name: &str, mut file_data: R) -> Result<(), Error>
+    where
+        R: Read + Seek,
+    {
+        let position = file_data.stream_position()?;
+        let end = file_data.seek(SeekFrom::End(0))?;
+        file_data.seek(SeekFrom::Start(position))?;
+
+        let file_size = end.saturating_sub(position);
+        let file_size = u32::try_from(file_size).map_err(|_| Error::FileDataTooLong {
+            size: file_size,
+            path: None,
+        })?;
+
+        self.write_file(file_name, file_size, file_data.take(u64::from(file_size)))
+    }
+
+    /// Write the file at `path` into the archive as `file_name`.
+    ///
+    /// The file is opened and its size is taken from its length,
+    /// as with [`Writer::write_file_verified`].
+    /// Files that are too large return [`Error::FileDataTooLong`] with `path`.
+    pub fn write_path(&mut self, file_name: &str, path: &Path) -> Result<(), Error> {
+        let file = std::fs::File::open(path)?;
+        self.write_path_data(file_name, path, std::io::BufReader::new(file))
+    }
+
+    /// Write `file_data`, opened from `path`, into the archive as `file_name`.
+    ///
+    /// This is split from [`Writer::write_path`] so tests can pass data that is not a real file.
+    fn write_path_data<R>(
+        &mut self,
+        file_name: &str,
+        path: &Path,
+        file_data: R,
+    ) -> Result<(), Error>
+    where
+        R: Read + Seek,
+    {
+        self.write_file_verified(file_name, file_data)
+            .map_err(|error| match error {
+                Error::FileDataTooLong { size, path: None } => Error::FileDataTooLong {
+                    size,
+                    path: Some(path.to_path_buf()),
+                },
+                error => error,
+            })
+    }
+
+    /// Write a collection of files, sorted by name.
+    ///
+    /// Each entry is a file name, size, and data, as passed to [`Writer::write_file`].
+    /// Since files are streamed, the entries must all be collected before writing in order to sort them.
+    /// Entries with equal names keep their relative order.
+    /// Unlike [`Writer::write_file`], this function cannot be retried.
+    pub fn write_entries_sorted<I, N, R>(&mut self, entries: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (N, u32, R)>,
+        N: AsRef<str>,
+        R: Read,
+    {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_by(|(a, _, _), (b, _, _)| a.as_ref().cmp(b.as_ref()));
+
+        for (file_name, file_size, file_data) in entries {
+            self.write_file(file_name.as_ref(), file_size, file_data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a collection of in-memory files, in iteration order.
+    ///
+    /// Each entry is a file name and its data.
+    /// For deterministic output, pass a sorted map, like a [`BTreeMap`](std::collections::BTreeMap).
+    /// Unlike [`Writer::write_file`], this function cannot be retried.
+    pub fn write_map<I, K, V>(&mut self, map: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
+        for (file_name, file_data) in map {
+            let file_data = file_data.as_ref();
+            let file_size =
+                u32::try_from(file_data.len()).map_err(|_| crate::sans_io::Error::ValueTooLarge)?;
+            self.write_file(file_name.as_ref(), file_size, file_data)?;
+        }
+
+        Ok(())
+    }
+
     /// Finish writing.
     ///
     /// This is only a convenience function to call the inner [`Write`] object's [`Write::flush`] method.
     pub fn finish(&mut self) -> Result<(), Error> {
+        let remaining = self.state_machine.remaining_file_data();
+        if remaining != 0 {
+            return Err(Error::IncompleteFile {
+                name: self.file_name.clone(),
+                remaining,
+            });
+        }
+
         match &mut self.state {
             State::FileHeader => {}
             _ => {
@@ -176,3 +492,604 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A writer that fails once, on the given write call.
+    struct FailOnceWriter {
+        inner: Vec<u8>,
+        calls: usize,
+        fail_on: usize,
+    }
+
+    impl Write for FailOnceWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.calls += 1;
+            if self.calls == self.fail_on {
+                return Err(std::io::Error::other("simulated failure"));
+            }
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    #[test]
+    fn writer_retry_fresh_source() {
+        let file_data: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        let file_size = u32::try_from(file_data.len()).expect("file data too large");
+
+        let mut expected = Vec::new();
+        let mut writer = Writer::new(&mut expected);
+        writer
+            .write_file("test", file_size, &*file_data)
+            .expect("failed to write file");
+        writer.finish().expect("failed to flush");
+
+        let mut writer = Writer::new(FailOnceWriter {
+            inner: Vec::new(),
+            calls: 0,
+            fail_on: 3,
+        });
+        assert!(writer.bytes_written_for_current_file() == 0);
+        writer
+            .write_file("test", file_size, &*file_data)
+            .expect_err("write should have failed");
+
+        // Retry with a fresh source, skipping the bytes already taken.
+        let skip = usize::try_from(writer.bytes_written_for_current_file()).unwrap();
+        assert!(skip > 0 && skip < file_data.len());
+        writer
+            .write_file("test", file_size, &file_data[skip..])
+            .expect("failed to write file");
+        assert!(writer.bytes_written_for_current_file() == 0);
+        writer.finish().expect("failed to flush");
+
+        assert!(writer.into_inner().inner == expected);
+    }
+
+    #[test]
+    fn writer_finish_incomplete_file() {
+        // The source ends early.
+        let mut writer = Writer::new_vec();
+        writer
+            .write_file("short", 10, &b"short"[..])
+            .expect("failed to write file");
+        let error = writer.finish().expect_err("file should be incomplete");
+        assert!(
+            matches!(&error, Error::IncompleteFile { name, remaining: 5 } if name == "short"),
+            "{error:?}"
+        );
+        assert!(error.to_string() == "file \"short\" is missing 5 bytes of data");
+
+        // The inner writer fails mid-file.
+        let file_data: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        let file_size = u32::try_from(file_data.len()).expect("file data too large");
+        let mut writer = Writer::new(FailOnceWriter {
+            inner: Vec::new(),
+            calls: 0,
+            fail_on: 3,
+        });
+        writer
+            .write_file("test", file_size, &*file_data)
+            .expect_err("write should have failed");
+        let error = writer.finish().expect_err("file should be incomplete");
+        assert!(
+            matches!(&error, Error::IncompleteFile { name, remaining } if name == "test" && *remaining > 0),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn writer_with_capacity() {
+        // The longest allowed name must still fit in the smallest buffer.
+        let name = "a".repeat(usize::try_from(crate::MAX_FILE_NAME_LEN).unwrap());
+        let data = vec![1; 3 * crate::MIN_BUFFER_CAPACITY];
+        let data_len = u32::try_from(data.len()).unwrap();
+
+        let mut writer = Writer::with_capacity(Cursor::new(Vec::new()), 0);
+        writer
+            .write_file(&name, data_len, &*data)
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let mut expected = Writer::new_vec();
+        expected
+            .write_file(&name, data_len, &*data)
+            .expect("failed to write file");
+        assert!(archive == expected.into_vec().expect("failed to finish"));
+
+        let mut reader = crate::Reader::with_capacity(Cursor::new(archive), 0);
+        reader.read_header().expect("failed to read header");
+        let mut file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        assert!(file.name() == name);
+        let mut actual = Vec::new();
+        file.read_to_end(&mut actual).expect("failed to read file");
+        assert!(actual == data);
+    }
+
+    #[test]
+    fn writer_reject_duplicates() {
+        let mut writer = Writer::new_vec();
+        writer
+            .write_file("a", 1, &b"a"[..])
+            .expect("failed to write file");
+        writer.set_reject_duplicates(true);
+        // Files written before enabling the check are not tracked.
+        writer
+            .write_file("a", 1, &b"a"[..])
+            .expect("failed to write file");
+        writer
+            .write_file("b", 1, &b"b"[..])
+            .expect("failed to write file");
+        let error = writer
+            .write_file("a", 1, &b"a"[..])
+            .expect_err("duplicate should be rejected");
+        assert!(matches!(&error, Error::DuplicateFileName { name } if name == "a"));
+
+        // Nothing is written for the rejected file, so the archive is still valid.
+        let archive = writer.into_vec().expect("failed to finish");
+        let mut reader = crate::Reader::new(Cursor::new(archive));
+        let names: Vec<String> = reader
+            .entries()
+            .expect("failed to list entries")
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert!(names == ["a", "a", "b"]);
+    }
+
+    #[test]
+    fn writer_bytes_written() {
+        let mut writer = Writer::new_vec();
+        assert!(writer.bytes_written() == 0);
+        writer.write_header().expect("failed to write header");
+        writer
+            .write_file("test", 4, &b"test"[..])
+            .expect("failed to write file");
+        writer
+            .write_file("empty", 0, &b""[..])
+            .expect("failed to write file");
+        let bytes_written = writer.bytes_written();
+        let archive = writer.into_vec().expect("failed to finish");
+
+        assert!(bytes_written == u64::try_from(archive.len()).unwrap());
+    }
+
+    #[test]
+    fn writer_hasher() {
+        use std::hash::Hasher;
+
+        let mut writer = Writer::new_vec();
+        assert!(writer.finalize_hash().is_none());
+        writer.set_hasher(std::hash::DefaultHasher::new());
+        let file_data: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        let file_size = u32::try_from(file_data.len()).expect("file data too large");
+        writer
+            .write_file("test", file_size, &*file_data)
+            .expect("failed to write file");
+        writer.finish().expect("failed to flush");
+        let hash = writer.finalize_hash().expect("missing hash");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let mut hasher = std::hash::DefaultHasher::new();
+        hasher.write(&archive);
+        assert!(hash == hasher.finish());
+    }
+
+    #[test]
+    fn writer_map() {
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("Data\\Actors.rvdata", b"actors".to_vec());
+        map.insert("Data\\Empty.rvdata", Vec::new());
+        map.insert("Game.ini", b"[Game]".to_vec());
+
+        let mut writer = Writer::new_vec();
+        writer.write_map(&map).expect("failed to write map");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let (files, error) = crate::Reader::new(Cursor::new(archive)).read_until_error();
+        assert!(error.is_none());
+        let read_map: std::collections::BTreeMap<_, _> = files.into_iter().collect();
+        assert!(read_map.len() == map.len());
+        for (file_name, file_data) in map.iter() {
+            assert!(read_map.get(*file_name) == Some(file_data));
+        }
+    }
+
+    #[test]
+    fn writer_try_into_inner() {
+        // Missing header
+        let writer = Writer::new_vec();
+        let (_writer, error) = writer
+            .try_into_inner()
+            .expect_err("header should be missing");
+        assert!(matches!(error, Error::NotFinished));
+
+        // Complete
+        let mut writer = Writer::new_vec();
+        writer
+            .write_file("test", 4, &b"test"[..])
+            .expect("failed to write file");
+        writer.try_into_inner().expect("archive should be complete");
+
+        // Partially written file
+        // The short file data is not detected until the next file is written.
+        let mut writer = Writer::new_vec();
+        let _ = writer.write_file("test", 4, &b"te"[..]);
+        let (_writer, error) = writer
+            .try_into_inner()
+            .expect_err("file should be partially written");
+        assert!(matches!(error, Error::NotFinished));
+    }
+
+    #[test]
+    fn writer_read_ahead() {
+        /// A reader that counts how many times it was read from.
+        struct CountingReader<'a> {
+            inner: &'a [u8],
+            reads: usize,
+        }
+
+        impl Read for CountingReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.reads += 1;
+                self.inner.read(buf)
+            }
+        }
+
+        let file_data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let file_size = u32::try_from(file_data.len()).expect("file data too large");
+
+        let mut archives = Vec::new();
+        let mut reads = Vec::new();
+        for read_ahead in [0, 256 * 1024] {
+            let mut writer = Writer::new_vec();
+            writer.set_read_ahead(read_ahead);
+            let mut source = CountingReader {
+                inner: &file_data,
+                reads: 0,
+            };
+            writer
+                .write_file("large", file_size, &mut source)
+                .expect("failed to write file");
+            writer
+                .write_file("small", 4, &b"test"[..])
+                .expect("failed to write file");
+            archives.push(writer.into_vec().expect("failed to finish"));
+            reads.push(source.reads);
+        }
+
+        assert!(archives[0] == archives[1]);
+        assert!(reads[1] < reads[0] / 10, "{reads:?}");
+    }
+
+    #[test]
+    fn writer_write_file_verified() {
+        let mut file_data = std::io::Cursor::new(b"skiptest".to_vec());
+        file_data.set_position(4);
+
+        // The claimed size does not match the remaining data.
+        let mut writer = Writer::new_vec();
+        writer
+            .write_file("test", 8, file_data.clone())
+            .expect("failed to write file");
+        let error = writer
+            .write_file("next", 0, std::io::empty())
+            .expect_err("size mismatch should be caught");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::FileDataSizeMismatch {
+                actual: 4,
+                expected: 8
+            })
+        ));
+
+        let mut writer = Writer::new_vec();
+        writer
+            .write_file_verified("test", &mut file_data)
+            .expect("failed to write file");
+        writer
+            .write_file("next", 0, std::io::empty())
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let mut reader = crate::Reader::new(std::io::Cursor::new(archive));
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        assert!(files[0].0 == "test");
+        assert!(files[0].1 == b"test");
+    }
+
+    #[test]
+    fn writer_write_path() {
+        /// Removes the file at its path when dropped, even if the test panics.
+        struct TempFile(std::path::PathBuf);
+
+        impl Drop for TempFile {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+
+        /// Data with a length, but no contents, so a huge file does not need to exist.
+        struct FakeLen {
+            position: u64,
+            len: u64,
+        }
+
+        impl Read for FakeLen {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                unreachable!("the data should not be read")
+            }
+        }
+
+        impl Seek for FakeLen {
+            fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+                self.position = match position {
+                    SeekFrom::Start(position) => position,
+                    SeekFrom::End(offset) => self.len.checked_add_signed(offset).unwrap(),
+                    SeekFrom::Current(offset) => self.position.checked_add_signed(offset).unwrap(),
+                };
+                Ok(self.position)
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "rgssad-writer-write-path-{}.txt",
+            std::process::id()
+        ));
+        let temp_file = TempFile(path.clone());
+        std::fs::write(&path, b"file data").expect("failed to write temp file");
+
+        let mut writer = Writer::new_vec();
+        writer
+            .write_path("Data\\test.txt", &path)
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+        drop(temp_file);
+
+        let mut reader = crate::Reader::new(std::io::Cursor::new(archive));
+        let (files, error) = reader.read_until_error();
+        assert!(error.is_none());
+        assert!(files == [("Data\\test.txt".to_string(), b"file data".to_vec())]);
+
+        let error = Writer::new_vec()
+            .write_path("missing", &path)
+            .expect_err("file should be missing");
+        assert!(matches!(error, Error::Io(error) if error.kind() == std::io::ErrorKind::NotFound));
+
+        let size = u64::from(u32::MAX) + 1;
+        let file_data = FakeLen {
+            position: 0,
+            len: size,
+        };
+        let mut writer = Writer::new_vec();
+        let error = writer
+            .write_path_data("large", &path, file_data)
+            .expect_err("file should be too large");
+        assert!(
+            matches!(&error, Error::FileDataTooLong { size: actual, path: Some(actual_path) } if *actual == size && *actual_path == path),
+            "{error:?}"
+        );
+        assert!(error.to_string().contains("is too large"));
+
+        // Nothing was written for the file.
+        assert!(writer.bytes_written_for_current_file() == 0);
+    }
+
+    #[test]
+    fn writer_vec() {
+        let mut writer = Writer::new_vec();
+        writer.write_header().expect("failed to write header");
+        writer
+            .write_file("test", 4, &b"test"[..])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        let mut reader = crate::Reader::new(Cursor::new(archive));
+        let mut file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).expect("failed to read file");
+        assert!(file.name() == "test");
+        assert!(buffer == b"test");
+    }
+
+    #[test]
+    fn writer_max_file_size() {
+        let mut writer = Writer::new_vec();
+        writer.set_max_file_size(Some(4));
+        writer
+            .write_file("ok", 4, &b"test"[..])
+            .expect("failed to write file");
+
+        let error = writer
+            .write_file("garbage", u32::MAX - 8, std::io::empty())
+            .expect_err("size should be too large");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::FileDataTooLarge { size, max: 4 })
+                if size == u32::MAX - 8
+        ));
+    }
+
+    #[test]
+    fn writer_with_key() {
+        let key = 0x1234_5678;
+        let mut writer = Writer::with_key(Cursor::new(Vec::new()), key);
+        writer
+            .write_file("test", 4, &b"test"[..])
+            .expect("failed to write file");
+        let archive = writer.into_vec().expect("failed to finish");
+
+        // The default key cannot read the archive.
+        let mut reader = crate::Reader::new(Cursor::new(archive.clone()));
+        assert!(reader.read_file().is_err());
+
+        let mut reader = crate::Reader::with_key(Cursor::new(archive), key);
+        let mut file = reader
+            .read_file()
+            .expect("failed to read file")
+            .expect("missing file");
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).expect("failed to read file");
+        assert!(file.name() == "test");
+        assert!(buffer == b"test");
+    }
+
+    /// A small deterministic PRNG, so the corpus is the same on every run.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            usize::try_from(self.next() % u64::try_from(n).unwrap()).unwrap()
+        }
+    }
+
+    /// Build a corpus of files, seeded with edge cases.
+    fn writer_corpus() -> Vec<Vec<(String, Vec<u8>)>> {
+        let max_name_len = usize::try_from(crate::MAX_FILE_NAME_LEN).unwrap();
+        let mut corpus = vec![
+            vec![],
+            vec![(String::new(), vec![])],
+            vec![("a".repeat(max_name_len), vec![0xFF; 3])],
+            vec![("big".into(), vec![0x55; 64 * 1024 + 3])],
+            vec![("a".into(), vec![]), ("a".into(), vec![1])],
+        ];
+
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+        for _ in 0..64 {
+            let num_files = rng.below(8);
+            let files = (0..num_files)
+                .map(|_| {
+                    let name_len = rng.below(max_name_len + 1);
+                    let name = (0..name_len)
+                        .map(|_| char::from(b'!' + u8::try_from(rng.below(94)).unwrap()))
+                        .collect();
+                    let data_len = rng.below(32 * 1024);
+                    let data = (0..data_len).map(|_| rng.next() as u8).collect();
+                    (name, data)
+                })
+                .collect();
+            corpus.push(files);
+        }
+
+        corpus
+    }
+
+    fn write_files(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+        let mut writer = Writer::new_vec();
+        writer.write_header().expect("failed to write header");
+        for (file_name, file_data) in files {
+            let len = u32::try_from(file_data.len()).expect("file data too large");
+            writer
+                .write_file(file_name, len, &**file_data)
+                .expect("failed to write file");
+        }
+        writer.into_vec().expect("failed to finish")
+    }
+
+    #[test]
+    fn writer_corpus_round_trip() {
+        for files in writer_corpus() {
+            let archive = write_files(&files);
+
+            let mut reader = crate::Reader::new(Cursor::new(&archive));
+            let mut read_files = Vec::new();
+            while let Some(mut file) = reader.read_file().expect("failed to read file") {
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer).expect("failed to read file");
+                read_files.push((file.name().to_string(), buffer));
+            }
+            assert!(read_files == files);
+
+            // Re-serializing must be byte-stable.
+            assert!(write_files(&read_files) == archive);
+        }
+    }
+
+    #[test]
+    fn writer_size_mismatch_does_not_panic() {
+        // More data than declared.
+        let mut writer = Writer::new_vec();
+        let error = writer
+            .write_file("test", 2, &b"test"[..])
+            .expect_err("data should be longer than declared");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::FileDataSizeMismatch {
+                actual: 4,
+                expected: 2
+            })
+        ));
+
+        // Less data than declared.
+        let mut writer = Writer::new_vec();
+        let _ = writer.write_file("test", u32::MAX, &b"test"[..]);
+        let error = writer
+            .write_file("next", 0, std::io::empty())
+            .expect_err("data should be shorter than declared");
+        assert!(matches!(
+            error,
+            Error::SansIo(crate::sans_io::Error::FileDataSizeMismatch {
+                actual: 4,
+                expected: u32::MAX
+            })
+        ));
+    }
+
+    #[test]
+    fn writer_entries_sorted() {
+        let files: [(&str, &[u8]); 3] = [
+            ("Data\\b.rvdata", b"b"),
+            ("Data\\a.rvdata", b"aa"),
+            ("Graphics\\c.png", b"ccc"),
+        ];
+
+        let write = |files: &[(&str, &[u8])]| {
+            let mut new_file = Vec::new();
+            let mut writer = Writer::new(&mut new_file);
+            writer.write_header().expect("failed to write header");
+            writer
+                .write_entries_sorted(files.iter().map(|(file_name, file_data)| {
+                    let len = u32::try_from(file_data.len()).expect("file data too large");
+                    (file_name, len, *file_data)
+                }))
+                .expect("failed to write files");
+            writer.finish().expect("failed to flush");
+            new_file
+        };
+
+        let forward = write(&files);
+        let mut reversed = files;
+        reversed.reverse();
+        let reversed = write(&reversed);
+        assert!(forward == reversed);
+
+        let mut reader = crate::Reader::new(std::io::Cursor::new(forward));
+        let manifest = reader.manifest().expect("failed to read manifest");
+        let names: Vec<_> = manifest
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert!(names == ["Data\\a.rvdata", "Data\\b.rvdata", "Graphics\\c.png"]);
+    }
+}